@@ -0,0 +1,342 @@
+//! The game's audio mixer: the channels sounds play on, the [`PlaySfx`] event
+//! for playing sounds, and the player's volume settings. How many sounds can
+//! play at once is up to [`voices`].
+
+use bevy::{audio::Volume, prelude::*, utils::HashMap};
+use serde::{Deserialize, Serialize};
+
+use crate::persistence;
+
+pub mod voices;
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<AudioChannel>();
+    app.register_type::<AudioMixer>();
+    app.init_resource::<AudioMixer>();
+    app.add_event::<PlaySfx>();
+    app.add_plugins(voices::plugin);
+
+    app.insert_resource(AudioSettings::load());
+    app.add_systems(
+        Update,
+        save_audio_settings.run_if(resource_changed::<AudioSettings>),
+    );
+    // Sounds start playing in `PostUpdate`. Catch them in the same frame, so
+    // they're only ever heard at the right volume.
+    app.add_systems(Last, apply_audio_settings);
+}
+
+/// Spatial audio uses the distance to attenuate the sound volume. In 2D with the default camera,
+/// 1 pixel is 1 unit of distance, so we use a scale so that 500 pixels is 1 unit of distance for
+/// audio. Sounds are at full volume within that, and fall off with the square of the distance
+/// past it.
+pub const AUDIO_SCALE: f32 = 1. / 500.0;
+/// Sounds with a position further than this from the [`SpatialListener`], in pixels, aren't
+/// played at all. They'd be down to a few percent of their volume.
+pub const MAX_AUDIBLE_DISTANCE: f32 = 2500.0;
+/// How far apart the [`SpatialListener`]'s ears are, in pixels. Panning comes from the
+/// difference in distance to each ear, so they're one audio unit apart to make it heard.
+pub const EAR_GAP: f32 = 1. / AUDIO_SCALE;
+
+/// Button sounds are heard a lot, so they're kept in the background.
+const UI_CHANNEL_VOLUME: f32 = 0.8;
+/// Other ducks are a little quieter than your own.
+const REMOTE_PLAYERS_CHANNEL_VOLUME: f32 = 0.8;
+
+/// The mixer channel a sound plays on. Every sound the game makes should have
+/// one, so it follows the player's volume settings and the [`AudioMixer`].
+///
+/// This can then be used to query for and operate on sounds on that channel. For example:
+///
+/// ```
+/// use bevy::prelude::*;
+/// use ducks_test::audio::AudioChannel;
+///
+/// fn stop_remote_player_sounds(mut commands: Commands, sound_query: Query<(Entity, &AudioChannel)>) {
+///     for (entity, channel) in &sound_query {
+///         if *channel == AudioChannel::RemotePlayers {
+///             commands.entity(entity).despawn_recursive();
+///         }
+///     }
+/// }
+/// ```
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Hash, Reflect)]
+#[reflect(Component)]
+pub enum AudioChannel {
+    /// Background music (ex: the soundtrack, the day and night tracks).
+    Music,
+    /// Sounds from your own duck and the world around it (ex: footsteps, crackers).
+    Sfx,
+    /// Menu and HUD sounds (ex: button hovers and presses).
+    Ui,
+    /// Sounds from other players' ducks, heard from where they are.
+    RemotePlayers,
+}
+
+impl AudioChannel {
+    /// The player's volume setting that applies to this channel.
+    pub fn volume_channel(self) -> VolumeChannel {
+        match self {
+            Self::Music => VolumeChannel::Music,
+            Self::Sfx | Self::Ui | Self::RemotePlayers => VolumeChannel::SoundEffects,
+        }
+    }
+}
+
+/// How loud each [`AudioChannel`] is in the mix, from 0 to 1, on top of the
+/// player's volume settings. Not saved.
+#[derive(Resource, Debug, Clone, PartialEq, Reflect)]
+#[reflect(Resource)]
+pub struct AudioMixer {
+    volumes: HashMap<AudioChannel, f32>,
+}
+
+impl Default for AudioMixer {
+    fn default() -> Self {
+        Self {
+            volumes: HashMap::from_iter([
+                (AudioChannel::Music, 1.0),
+                (AudioChannel::Sfx, 1.0),
+                (AudioChannel::Ui, UI_CHANNEL_VOLUME),
+                (AudioChannel::RemotePlayers, REMOTE_PLAYERS_CHANNEL_VOLUME),
+            ]),
+        }
+    }
+}
+
+impl AudioMixer {
+    pub fn volume(&self, channel: AudioChannel) -> f32 {
+        self.volumes.get(&channel).copied().unwrap_or(1.0)
+    }
+
+    pub fn set_volume(&mut self, channel: AudioChannel, volume: f32) {
+        self.volumes.insert(channel, volume.clamp(0.0, 1.0));
+    }
+}
+
+/// Plays a one-off sound on a channel. This is the way to make a sound, rather
+/// than spawning an [`AudioBundle`].
+///
+/// ```
+/// use bevy::prelude::*;
+/// use ducks_test::audio::{AudioChannel, PlaySfx};
+///
+/// fn play_boing(asset_server: Res<AssetServer>, mut play_sfx: EventWriter<PlaySfx>) {
+///     let boing = asset_server.load("audio/sound_effects/boing.ogg");
+///     play_sfx.send(PlaySfx::new(AudioChannel::Sfx, boing).with_speed(1.5));
+/// }
+/// ```
+#[derive(Event, Debug, Clone)]
+pub struct PlaySfx {
+    pub source: Handle<AudioSource>,
+    pub channel: AudioChannel,
+    pub speed: f32,
+    /// Before the channel and the player's volume settings.
+    pub volume: f32,
+    pub priority: SfxPriority,
+    /// Where the sound comes from in the world. Sounds with a position are
+    /// spatial: attenuated and panned from the [`SpatialListener`] on the
+    /// player's duck, and not played at all past [`MAX_AUDIBLE_DISTANCE`].
+    pub position: Option<Vec2>,
+    /// An entity the sound moves with while it plays, like the duck that
+    /// quacked. It stays where it was heard last if the entity goes away.
+    pub following: Option<Entity>,
+}
+
+impl PlaySfx {
+    pub fn new(channel: AudioChannel, source: Handle<AudioSource>) -> Self {
+        Self {
+            source,
+            channel,
+            speed: 1.0,
+            volume: 1.0,
+            priority: SfxPriority::Normal,
+            position: None,
+            following: None,
+        }
+    }
+
+    pub fn with_speed(mut self, speed: f32) -> Self {
+        self.speed = speed;
+        self
+    }
+
+    pub fn with_volume(mut self, volume: f32) -> Self {
+        self.volume = volume;
+        self
+    }
+
+    pub fn with_priority(mut self, priority: SfxPriority) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    pub fn at(mut self, position: Vec2) -> Self {
+        self.position = Some(position);
+        self
+    }
+
+    /// Only moves sounds that have a position, see [`PlaySfx::at`].
+    pub fn following(mut self, entity: Entity) -> Self {
+        self.following = Some(entity);
+        self
+    }
+}
+
+/// How much a sound matters when there are too many to play at once. Closer
+/// sounds matter more within the same priority.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default, Reflect)]
+pub enum SfxPriority {
+    /// Sounds that happen all the time, like footsteps.
+    Low,
+    #[default]
+    Normal,
+    /// Sounds a player made on purpose, like quacks and emotes.
+    High,
+}
+
+/// A volume the player can set on the settings screen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VolumeChannel {
+    /// Everything, on top of the other channels.
+    Master,
+    Music,
+    SoundEffects,
+}
+
+impl VolumeChannel {
+    pub const ALL: [Self; 3] = [Self::Master, Self::Music, Self::SoundEffects];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Master => "Master",
+            Self::Music => "Music",
+            Self::SoundEffects => "Sound effects",
+        }
+    }
+}
+
+/// Volumes, from 0 to 1, and whether each [`VolumeChannel`] is muted. Sounds
+/// with an [`AudioChannel`] play at the volume in their [`PlaybackSettings`]
+/// scaled by these and the [`AudioMixer`], so change that to fade them.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AudioSettings {
+    pub master: f32,
+    pub music: f32,
+    pub sound_effects: f32,
+    pub master_muted: bool,
+    pub music_muted: bool,
+    pub sound_effects_muted: bool,
+}
+
+impl Default for AudioSettings {
+    fn default() -> Self {
+        Self {
+            master: 0.3,
+            music: 1.0,
+            sound_effects: 1.0,
+            master_muted: false,
+            music_muted: false,
+            sound_effects_muted: false,
+        }
+    }
+}
+
+impl AudioSettings {
+    const SAVE_KEY: &'static str = "audio_settings";
+
+    fn load() -> Self {
+        persistence::load(Self::SAVE_KEY).unwrap_or_default()
+    }
+
+    pub fn volume(&self, channel: VolumeChannel) -> f32 {
+        match channel {
+            VolumeChannel::Master => self.master,
+            VolumeChannel::Music => self.music,
+            VolumeChannel::SoundEffects => self.sound_effects,
+        }
+    }
+
+    pub fn set_volume(&mut self, channel: VolumeChannel, volume: f32) {
+        let volume = volume.clamp(0.0, 1.0);
+        match channel {
+            VolumeChannel::Master => self.master = volume,
+            VolumeChannel::Music => self.music = volume,
+            VolumeChannel::SoundEffects => self.sound_effects = volume,
+        }
+    }
+
+    pub fn muted(&self, channel: VolumeChannel) -> bool {
+        match channel {
+            VolumeChannel::Master => self.master_muted,
+            VolumeChannel::Music => self.music_muted,
+            VolumeChannel::SoundEffects => self.sound_effects_muted,
+        }
+    }
+
+    pub fn set_muted(&mut self, channel: VolumeChannel, muted: bool) {
+        match channel {
+            VolumeChannel::Master => self.master_muted = muted,
+            VolumeChannel::Music => self.music_muted = muted,
+            VolumeChannel::SoundEffects => self.sound_effects_muted = muted,
+        }
+    }
+
+    /// The volume a channel actually plays at, after mutes and the master
+    /// volume.
+    pub fn effective_volume(&self, channel: VolumeChannel) -> f32 {
+        let audible = |channel| {
+            if self.muted(channel) {
+                0.0
+            } else {
+                self.volume(channel)
+            }
+        };
+        match channel {
+            VolumeChannel::Master => audible(VolumeChannel::Master),
+            _ => audible(VolumeChannel::Master) * audible(channel),
+        }
+    }
+}
+
+fn save_audio_settings(settings: Res<AudioSettings>) {
+    if settings.is_added() {
+        return;
+    }
+    persistence::save(AudioSettings::SAVE_KEY, &*settings);
+}
+
+fn apply_audio_settings(
+    settings: Res<AudioSettings>,
+    mixer: Res<AudioMixer>,
+    mut global_volume: ResMut<GlobalVolume>,
+    sound_query: Query<(
+        &AudioChannel,
+        &PlaybackSettings,
+        Option<&AudioSink>,
+        Option<&SpatialAudioSink>,
+    )>,
+) {
+    // Sounds without a channel still follow the master volume.
+    let master = settings.effective_volume(VolumeChannel::Master);
+    if global_volume.volume.get() != master {
+        global_volume.volume = Volume::new(master);
+    }
+
+    for (channel, playback, sink, spatial_sink) in &sound_query {
+        let volume = playback.volume.get()
+            * settings.effective_volume(channel.volume_channel())
+            * mixer.volume(*channel);
+        set_sink_volume(volume, sink, spatial_sink);
+    }
+}
+
+fn set_sink_volume(volume: f32, sink: Option<&AudioSink>, spatial_sink: Option<&SpatialAudioSink>) {
+    if let Some(sink) = sink {
+        sink.set_volume(volume);
+    }
+    if let Some(sink) = spatial_sink {
+        sink.set_volume(volume);
+    }
+}
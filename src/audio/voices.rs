@@ -0,0 +1,252 @@
+//! The sound budget. [`PlaySfx`] events play on a fixed pool of voices per
+//! [`AudioChannel`], so a crowd of ducks can't pile up hundreds of overlapping
+//! sounds.
+//!
+//! When a channel has no free voice, the new sound takes over the one that
+//! matters least, if it matters more: louder [`SfxPriority`] first, then the
+//! closer of the two. The same sound asked for again straight away from the
+//! same place is only played once. [`VoiceStats`] counts all of this for the
+//! dev tools.
+
+use bevy::{audio::Volume, prelude::*, utils::HashMap};
+
+use crate::AppSet;
+
+use super::{AudioChannel, PlaySfx, SfxPriority, MAX_AUDIBLE_DISTANCE};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<VoiceStats>();
+    app.init_resource::<VoiceStats>();
+    app.add_systems(Startup, spawn_voices);
+    // After gameplay has had a chance to ask for sounds this frame.
+    app.add_systems(
+        Update,
+        (play_sfx, follow_sound_sources)
+            .chain()
+            .after(AppSet::Update),
+    );
+}
+
+/// How long after a sound the same sound from the same place is ignored, in
+/// seconds.
+const DUPLICATE_WINDOW: f32 = 0.05;
+/// How close two of the same sound have to be to count as one, in pixels.
+const DUPLICATE_DISTANCE: f32 = 64.0;
+
+/// How many sounds can play at once on a channel. Music is played by the
+/// `music` module, outside of the budget.
+pub fn voice_limit(channel: AudioChannel) -> Option<usize> {
+    match channel {
+        AudioChannel::Music => None,
+        AudioChannel::Sfx => Some(12),
+        AudioChannel::Ui => Some(4),
+        AudioChannel::RemotePlayers => Some(24),
+    }
+}
+
+/// Counters for the sound budget, since the game started.
+#[derive(Resource, Debug, Clone, Default, PartialEq, Reflect)]
+#[reflect(Resource)]
+pub struct VoiceStats {
+    /// Voices in use right now, by channel.
+    pub playing: HashMap<AudioChannel, usize>,
+    pub played: u64,
+    /// Sounds cut off to make room for one that mattered more.
+    pub stolen: u64,
+    /// Sounds not played because every voice was busy with ones that
+    /// mattered more.
+    pub dropped_over_budget: u64,
+    pub dropped_duplicate: u64,
+    pub dropped_out_of_range: u64,
+}
+
+/// An entity that plays one sound at a time, reused from sound to sound.
+#[derive(Component, Debug)]
+struct Voice {
+    playing: bool,
+    /// How much the sound playing matters, see [`sound_score`].
+    score: f32,
+    started: f32,
+    following: Option<Entity>,
+}
+
+/// A sound that was played recently, to suppress duplicates of it.
+struct RecentSound {
+    source: AssetId<AudioSource>,
+    channel: AudioChannel,
+    position: Option<Vec2>,
+    at: f32,
+}
+
+fn spawn_voices(mut commands: Commands) {
+    for channel in [
+        AudioChannel::Sfx,
+        AudioChannel::Ui,
+        AudioChannel::RemotePlayers,
+    ] {
+        for _ in 0..voice_limit(channel).unwrap_or_default() {
+            commands.spawn((
+                Name::new(format!("{channel:?} voice")),
+                Voice {
+                    playing: false,
+                    score: 0.0,
+                    started: 0.0,
+                    following: None,
+                },
+                channel,
+                SpatialBundle::default(),
+            ));
+        }
+    }
+}
+
+/// Higher for sounds that matter more. Priority always wins over distance.
+fn sound_score(priority: SfxPriority, distance: Option<f32>) -> f32 {
+    let closeness = distance.map_or(1.0, |distance| {
+        1.0 - (distance / MAX_AUDIBLE_DISTANCE).min(1.0)
+    });
+    priority as u8 as f32 * 2.0 + closeness
+}
+
+fn play_sfx(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut event_reader: EventReader<PlaySfx>,
+    listener_query: Query<&GlobalTransform, With<SpatialListener>>,
+    mut voice_query: Query<(
+        Entity,
+        &AudioChannel,
+        &mut Voice,
+        &mut Transform,
+        Has<Handle<AudioSource>>,
+        Option<&AudioSink>,
+        Option<&SpatialAudioSink>,
+    )>,
+    mut recent: Local<Vec<RecentSound>>,
+    mut stats: ResMut<VoiceStats>,
+) {
+    let now = time.elapsed_seconds();
+    let listener = listener_query
+        .get_single()
+        .ok()
+        .map(|transform| transform.translation().truncate());
+
+    // Finished sounds have their audio components removed, which frees the
+    // voice for the next one.
+    for (_, _, mut voice, _, has_source, _, _) in &mut voice_query {
+        if voice.playing && !has_source {
+            voice.playing = false;
+        }
+    }
+    recent.retain(|sound| now - sound.at < DUPLICATE_WINDOW);
+
+    for e in event_reader.read() {
+        let distance = match (e.position, listener) {
+            (Some(position), Some(listener)) => Some(listener.distance(position)),
+            // Without a duck to hear it from, there's nothing to hear.
+            (Some(_), None) => Some(f32::INFINITY),
+            (None, _) => None,
+        };
+        if distance.is_some_and(|distance| distance > MAX_AUDIBLE_DISTANCE) {
+            stats.dropped_out_of_range += 1;
+            continue;
+        }
+
+        let duplicate = recent.iter().any(|sound| {
+            sound.source == e.source.id()
+                && sound.channel == e.channel
+                && match (sound.position, e.position) {
+                    (Some(a), Some(b)) => a.distance(b) < DUPLICATE_DISTANCE,
+                    (None, None) => true,
+                    _ => false,
+                }
+        });
+        if duplicate {
+            stats.dropped_duplicate += 1;
+            continue;
+        }
+
+        // A free voice if there is one, or else the one that matters least,
+        // and the oldest of those.
+        let score = sound_score(e.priority, distance);
+        let Some((entity, _, mut voice, mut transform, _, sink, spatial_sink)) = voice_query
+            .iter_mut()
+            .filter(|(_, channel, ..)| **channel == e.channel)
+            .min_by(|(_, _, a, ..), (_, _, b, ..)| {
+                a.playing
+                    .cmp(&b.playing)
+                    .then(a.score.total_cmp(&b.score))
+                    .then(a.started.total_cmp(&b.started))
+            })
+        else {
+            warn!("No voices for {:?} sounds", e.channel);
+            continue;
+        };
+        if voice.playing {
+            if score < voice.score {
+                stats.dropped_over_budget += 1;
+                continue;
+            }
+            stats.stolen += 1;
+        }
+
+        if let Some(sink) = sink {
+            sink.stop();
+        }
+        if let Some(sink) = spatial_sink {
+            sink.stop();
+        }
+        *voice = Voice {
+            playing: true,
+            score,
+            started: now,
+            following: e.position.and(e.following),
+        };
+        transform.translation = e.position.unwrap_or_default().extend(0.0);
+        // Without a sink, the new sound starts playing in `PostUpdate`.
+        commands
+            .entity(entity)
+            .remove::<(AudioSink, SpatialAudioSink)>()
+            .insert(AudioBundle {
+                source: e.source.clone(),
+                settings: PlaybackSettings::REMOVE
+                    .with_speed(e.speed)
+                    .with_volume(Volume::new(e.volume))
+                    .with_spatial(e.position.is_some()),
+            });
+        stats.played += 1;
+        recent.push(RecentSound {
+            source: e.source.id(),
+            channel: e.channel,
+            position: e.position,
+            at: now,
+        });
+    }
+
+    let mut playing = HashMap::default();
+    for (_, channel, voice, ..) in &voice_query {
+        if voice.playing {
+            *playing.entry(*channel).or_default() += 1;
+        }
+    }
+    if stats.playing != playing {
+        stats.playing = playing;
+    }
+}
+
+/// Keep spatial sounds with the entity they come from, like a duck that's
+/// still walking.
+fn follow_sound_sources(
+    mut voice_query: Query<(&mut Voice, &mut Transform)>,
+    source_query: Query<&GlobalTransform, Without<Voice>>,
+) {
+    for (mut voice, mut transform) in &mut voice_query {
+        let Some(following) = voice.following.filter(|_| voice.playing) else {
+            continue;
+        };
+        match source_query.get(following) {
+            Ok(source) => transform.translation = source.translation().truncate().extend(0.0),
+            Err(_) => voice.following = None,
+        }
+    }
+}
@@ -1,25 +1,191 @@
-use bevy::prelude::*;
+//! Background and foreground layers of the level.
+//!
+//! Each layer scrolls with a [`Parallax`] factor relative to the camera, so
+//! far layers drift by slower than the world and near ones faster. Layers can
+//! repeat their image to fill the view at any zoom level. Foreground layers
+//! are drawn over the ducks, and fade out while the player is behind them.
+
+use bevy::{prelude::*, render::camera::CameraUpdateSystem, transform::TransformSystem};
+
+use crate::AppSet;
+
+use super::{camera::CameraFollowSet, level::LevelSprite, player::Player};
 
 pub(super) fn plugin(app: &mut App) {
-    app.add_systems(Startup, create_background);
+    app.register_type::<BackgroundLayer>();
+    app.register_type::<ForegroundLayer>();
+    app.register_type::<Parallax>();
+    app.register_type::<RepeatingLayer>();
+
+    app.add_systems(Update, fade_foreground_layers.in_set(AppSet::Update));
+
+    // Like the camera, move layers before transforms are propagated so they
+    // don't lag a frame behind.
+    app.add_systems(
+        PostUpdate,
+        scroll_layers
+            .after(CameraFollowSet)
+            .after(CameraUpdateSystem)
+            .before(TransformSystem::TransformPropagate),
+    );
 }
 
-fn create_background(
-    mut commands: Commands,
-    asset_server: Res<AssetServer>,
-) {
-    let texture_handle = asset_server.load("images/ducks-bg-test.png");
+/// How see-through a foreground layer gets while the player is behind it.
+const FOREGROUND_FADED_ALPHA: f32 = 0.35;
+/// How quickly foreground layers fade, in alpha per second.
+const FOREGROUND_FADE_SPEED: f32 = 4.0;
+
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Default, Reflect)]
+#[reflect(Component)]
+pub struct BackgroundLayer;
+
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Default, Reflect)]
+#[reflect(Component)]
+pub struct ForegroundLayer;
+
+/// Moves a layer relative to the camera.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Reflect)]
+#[reflect(Component)]
+pub struct Parallax {
+    /// Where the layer is when the camera is at the origin.
+    pub origin: Vec2,
+    /// How much the layer moves with the world: 1 sticks to the world, 0
+    /// sticks to the screen, and above 1 is closer than the ducks.
+    pub factor: f32,
+}
+
+/// A layer that repeats its image to cover the whole view.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Default, Reflect)]
+#[reflect(Component)]
+pub struct RepeatingLayer;
+
+impl RepeatingLayer {
+    pub fn bundle() -> impl Bundle {
+        (
+            RepeatingLayer,
+            ImageScaleMode::Tiled {
+                tile_x: true,
+                tile_y: true,
+                stretch_value: 1.0,
+            },
+        )
+    }
+}
+
+/// The components for one background layer of the level, drawn behind
+/// everything else.
+pub fn background_layer(layer: &LevelSprite) -> impl Bundle {
+    (
+        Name::new("Background layer"),
+        BackgroundLayer,
+        layer_sprite(layer),
+    )
+}
 
-    println!("creating background...");
+/// The components for one foreground layer of the level, drawn in front of
+/// the ducks.
+pub fn foreground_layer(layer: &LevelSprite) -> impl Bundle {
+    (
+        Name::new("Foreground layer"),
+        ForegroundLayer,
+        layer_sprite(layer),
+    )
+}
 
-    // Spawn an entity with the background image as a sprite
-    commands.spawn(SpriteBundle {
-        texture: texture_handle,
-        transform: Transform {
-            scale: Vec3::new(1.0, 1.0, 0.0),
-            translation: Vec3::new(0.0, 0.0, 1.0),
+fn layer_sprite(layer: &LevelSprite) -> impl Bundle {
+    (
+        Parallax {
+            origin: layer.position,
+            factor: layer.parallax,
+        },
+        SpriteBundle {
+            texture: layer.image.clone(),
+            transform: Transform {
+                scale: Vec2::splat(layer.scale).extend(1.0),
+                translation: layer.position.extend(layer.z),
+                ..Default::default()
+            },
             ..Default::default()
         },
-        ..Default::default()
-    });
+    )
+}
+
+fn scroll_layers(
+    images: Res<Assets<Image>>,
+    camera_query: Query<(&Transform, &OrthographicProjection), (With<Camera>, Without<Parallax>)>,
+    mut layer_query: Query<(
+        &Parallax,
+        &Handle<Image>,
+        &mut Transform,
+        &mut Sprite,
+        Has<RepeatingLayer>,
+    )>,
+) {
+    let Ok((camera_transform, projection)) = camera_query.get_single() else {
+        return;
+    };
+    let camera = camera_transform.translation.truncate();
+
+    for (parallax, image, mut transform, mut sprite, repeating) in &mut layer_query {
+        let mut position = parallax.origin + camera * (1.0 - parallax.factor);
+
+        if repeating {
+            let Some(image) = images.get(image) else {
+                continue;
+            };
+            let scale = transform.scale.truncate();
+            let tile = image.size().as_vec2() * scale;
+            // Moving by whole tiles looks the same, so keep the layer centered
+            // under the camera.
+            position += ((camera - position) / tile).round() * tile;
+            // Cover the view with a tile to spare on each side. An odd number
+            // of tiles keeps the pattern in place when the size changes.
+            let tiles = (projection.area.size() / tile).ceil() + 2.0;
+            let tiles = tiles + (1.0 - tiles % 2.0);
+            let size = tiles * tile / scale;
+            if sprite.custom_size != Some(size) {
+                sprite.custom_size = Some(size);
+            }
+        }
+
+        transform.translation.x = position.x;
+        transform.translation.y = position.y;
+    }
+}
+
+fn fade_foreground_layers(
+    time: Res<Time>,
+    images: Res<Assets<Image>>,
+    player_query: Query<&Transform, (With<Player>, Without<ForegroundLayer>)>,
+    mut layer_query: Query<(&Handle<Image>, &Transform, &mut Sprite), With<ForegroundLayer>>,
+) {
+    let player = player_query
+        .get_single()
+        .ok()
+        .map(|transform| transform.translation.truncate());
+
+    for (image, transform, mut sprite) in &mut layer_query {
+        let Some(size) = sprite
+            .custom_size
+            .or_else(|| images.get(image).map(|image| image.size().as_vec2()))
+        else {
+            continue;
+        };
+        let area = Rect::from_center_size(
+            transform.translation.truncate(),
+            size * transform.scale.truncate(),
+        );
+        let target = match player {
+            Some(player) if area.contains(player) => FOREGROUND_FADED_ALPHA,
+            _ => 1.0,
+        };
+
+        let alpha = sprite.color.alpha();
+        if alpha != target {
+            let step = FOREGROUND_FADE_SPEED * time.delta_seconds();
+            sprite
+                .color
+                .set_alpha(alpha + (target - alpha).clamp(-step, step));
+        }
+    }
 }
@@ -0,0 +1,239 @@
+//! Temporary power-ups from eating special crackers.
+//!
+//! Eating a [`CrackerKind`] with a [`Buff`] starts (or restarts) its timer.
+//! While it runs, the buff changes your duck's [`MovementController`] or
+//! [`PickupRadius`], and an icon with a countdown is shown on the HUD.
+
+use bevy::prelude::*;
+
+use crate::{screens::Screen, AppSet};
+
+use super::{
+    cracker::{CrackerKind, PickupRadius, BASE_PICKUP_RADIUS},
+    movement::MovementController,
+    player::{Player, DUCK_MAX_SPEED},
+    websocket_connect::MoveCrackersBevyEvent,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<ActiveBuffs>();
+
+    app.add_systems(OnEnter(Screen::Gameplay), spawn_buff_hud);
+    app.add_systems(OnExit(Screen::Gameplay), clear_buffs);
+    app.add_systems(
+        Update,
+        (
+            tick_buffs.in_set(AppSet::TickTimers),
+            (start_eaten_buffs, apply_buffs, update_buff_hud)
+                .chain()
+                .in_set(AppSet::Update),
+        )
+            .run_if(in_state(Screen::Gameplay)),
+    );
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Buff {
+    /// Raises your top speed.
+    Speed,
+    /// Widens your pickup radius.
+    Magnet,
+}
+
+impl Buff {
+    fn seconds(self) -> f32 {
+        match self {
+            Self::Speed => 8.0,
+            Self::Magnet => 12.0,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::Speed => "Speed",
+            Self::Magnet => "Magnet",
+        }
+    }
+
+    /// The cracker the buff comes from, for its HUD icon.
+    fn cracker_kind(self) -> CrackerKind {
+        match self {
+            Self::Speed => CrackerKind::SpeedSeed,
+            Self::Magnet => CrackerKind::MagnetBread,
+        }
+    }
+}
+
+const SPEED_BUFF_MULTIPLIER: f32 = 1.6;
+const MAGNET_PICKUP_RADIUS: f32 = 200.0;
+
+/// The buffs your duck currently has, with the time left on each.
+#[derive(Resource, Debug, Default)]
+pub struct ActiveBuffs(pub Vec<(Buff, Timer)>);
+
+impl ActiveBuffs {
+    pub fn has(&self, buff: Buff) -> bool {
+        self.0.iter().any(|(active, _)| *active == buff)
+    }
+
+    /// Start a buff, or restart it if it's already active.
+    fn start(&mut self, buff: Buff) {
+        let timer = Timer::from_seconds(buff.seconds(), TimerMode::Once);
+        match self.0.iter_mut().find(|(active, _)| *active == buff) {
+            Some((_, active_timer)) => *active_timer = timer,
+            None => self.0.push((buff, timer)),
+        }
+    }
+}
+
+fn clear_buffs(mut buffs: ResMut<ActiveBuffs>) {
+    buffs.0.clear();
+}
+
+fn tick_buffs(time: Res<Time>, mut buffs: ResMut<ActiveBuffs>) {
+    for (_, timer) in &mut buffs.0 {
+        timer.tick(time.delta());
+    }
+    buffs.0.retain(|(_, timer)| !timer.finished());
+}
+
+fn start_eaten_buffs(
+    mut bevy_move_crackers_event_reader: EventReader<MoveCrackersBevyEvent>,
+    mut buffs: ResMut<ActiveBuffs>,
+) {
+    for e in bevy_move_crackers_event_reader.read() {
+        if !e.you_got_crackers {
+            continue;
+        }
+        if let Some(buff) = e.eaten_kind.buff() {
+            info!("Got the {} buff!", buff.label());
+            buffs.start(buff);
+        }
+    }
+}
+
+fn apply_buffs(
+    buffs: Res<ActiveBuffs>,
+    mut player_query: Query<(&mut MovementController, &mut PickupRadius), With<Player>>,
+) {
+    let max_speed = if buffs.has(Buff::Speed) {
+        DUCK_MAX_SPEED * SPEED_BUFF_MULTIPLIER
+    } else {
+        DUCK_MAX_SPEED
+    };
+    let pickup_radius = if buffs.has(Buff::Magnet) {
+        MAGNET_PICKUP_RADIUS
+    } else {
+        BASE_PICKUP_RADIUS
+    };
+
+    for (mut controller, mut radius) in &mut player_query {
+        if controller.max_speed != max_speed {
+            controller.max_speed = max_speed;
+        }
+        radius.set_if_neq(PickupRadius(pickup_radius));
+    }
+}
+
+#[derive(Component)]
+struct BuffHud;
+
+/// A buff's icon on the HUD.
+#[derive(Component)]
+struct BuffIcon(Buff);
+
+#[derive(Component)]
+struct BuffCountdownText;
+
+fn spawn_buff_hud(mut commands: Commands) {
+    commands.spawn((
+        Name::new("Buff HUD"),
+        BuffHud,
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                left: Val::Percent(3.),
+                top: Val::Percent(10.),
+                column_gap: Val::Px(10.0),
+                ..default()
+            },
+            ..default()
+        },
+        StateScoped(Screen::Gameplay),
+    ));
+}
+
+fn update_buff_hud(
+    mut commands: Commands,
+    buffs: Res<ActiveBuffs>,
+    asset_server: Res<AssetServer>,
+    hud_query: Query<Entity, With<BuffHud>>,
+    icon_query: Query<(Entity, &BuffIcon, &Children)>,
+    mut text_query: Query<&mut Text, With<BuffCountdownText>>,
+) {
+    let Ok(hud) = hud_query.get_single() else {
+        return;
+    };
+
+    for (entity, icon, children) in &icon_query {
+        let Some((_, timer)) = buffs.0.iter().find(|(buff, _)| *buff == icon.0) else {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        };
+        for child in children {
+            if let Ok(mut text) = text_query.get_mut(*child) {
+                let countdown = format_countdown(timer);
+                if text.sections[0].value != countdown {
+                    text.sections[0].value = countdown;
+                }
+            }
+        }
+    }
+
+    for (buff, timer) in &buffs.0 {
+        if icon_query.iter().any(|(_, icon, _)| icon.0 == *buff) {
+            continue;
+        }
+        commands.entity(hud).with_children(|hud| {
+            hud.spawn((
+                Name::new(format!("{} buff icon", buff.label())),
+                BuffIcon(*buff),
+                NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    ..default()
+                },
+            ))
+            .with_children(|icon| {
+                icon.spawn(ImageBundle {
+                    style: Style {
+                        width: Val::Px(40.0),
+                        height: Val::Px(20.0),
+                        ..default()
+                    },
+                    image: UiImage::new(asset_server.load("images/cracker-v1.png"))
+                        .with_color(buff.cracker_kind().tint()),
+                    ..default()
+                });
+                icon.spawn((
+                    BuffCountdownText,
+                    TextBundle::from_section(
+                        format_countdown(timer),
+                        TextStyle {
+                            font: asset_server.load("FiraSans-Bold.ttf"),
+                            font_size: 18.0,
+                            color: Color::WHITE,
+                        },
+                    ),
+                ));
+            });
+        });
+    }
+}
+
+fn format_countdown(timer: &Timer) -> String {
+    format!("{:.0}s", timer.remaining_secs().ceil())
+}
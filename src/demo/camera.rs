@@ -0,0 +1,267 @@
+//! A camera rig that follows the [`Player`].
+//!
+//! The duck can move freely inside a deadzone around the middle of the screen
+//! before the camera starts following. The camera then eases towards the duck,
+//! looking ahead in the direction it's moving. The camera is also clamped so
+//! that the edges of the view, not just its centre, stay inside the
+//! [`LevelBounds`]. This is rechecked every frame, so it also holds after the
+//! window is resized.
+//!
+//! Players can zoom with the mouse wheel or a two-finger pinch. The zoom level
+//! is saved between sessions.
+
+use bevy::{
+    input::{
+        mouse::{MouseScrollUnit, MouseWheel},
+        touch::Touches,
+    },
+    prelude::*,
+    render::camera::CameraUpdateSystem,
+    transform::TransformSystem,
+    window::PrimaryWindow,
+};
+
+use crate::{input::input_map::gameplay_input_allowed, persistence, screens::Screen, AppSet};
+
+use super::{
+    chat::chat_log_hovered, level::LevelBounds, movement::MovementController, player::Player,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<CameraRig>();
+    app.init_resource::<CameraRig>();
+    app.register_type::<CameraZoom>();
+    app.insert_resource(CameraZoom::load());
+    app.init_resource::<Pinching>();
+
+    app.add_systems(
+        Update,
+        (
+            (zoom_with_mouse_wheel.run_if(not(chat_log_hovered)), zoom_with_pinch)
+                .run_if(gameplay_input_allowed)
+                .in_set(AppSet::RecordInput)
+                .run_if(in_state(Screen::Gameplay)),
+            apply_zoom.in_set(AppSet::Update),
+            save_camera_zoom,
+        ),
+    );
+
+    // Run after the projection has been updated for the window size, but
+    // before transforms are propagated so the new position is drawn this frame.
+    app.add_systems(
+        PostUpdate,
+        (snap_to_joined_player, follow_player)
+            .chain()
+            .in_set(CameraFollowSet)
+            .after(CameraUpdateSystem)
+            .before(TransformSystem::TransformPropagate),
+    );
+}
+
+/// The camera is moved to where it's drawn this frame. Things that follow the
+/// camera should run after this.
+#[derive(SystemSet, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CameraFollowSet;
+
+/// How the camera follows the player.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Reflect)]
+#[reflect(Resource)]
+pub struct CameraRig {
+    /// Half the size of the area around the centre of the view the duck can
+    /// move in without the camera following.
+    pub deadzone: Vec2,
+    /// How quickly the camera catches up, per second. Higher is snappier.
+    pub smoothing: f32,
+    /// How far ahead to look, in seconds of the duck's current velocity.
+    pub lookahead: f32,
+    /// The closest the player can zoom in, as a projection scale.
+    pub min_zoom: f32,
+    /// The furthest the player can zoom out, as a projection scale.
+    pub max_zoom: f32,
+}
+
+impl Default for CameraRig {
+    fn default() -> Self {
+        Self {
+            deadzone: Vec2::new(80.0, 50.0),
+            smoothing: 6.0,
+            lookahead: 0.3,
+            min_zoom: 0.5,
+            max_zoom: 2.5,
+        }
+    }
+}
+
+/// The zoom level the player picked, as the camera's projection scale: bigger
+/// values show more of the level. The camera never zooms out further than the
+/// level is big, whatever this is set to.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Reflect)]
+#[reflect(Resource)]
+pub struct CameraZoom(pub f32);
+
+impl Default for CameraZoom {
+    fn default() -> Self {
+        Self(1.0)
+    }
+}
+
+impl CameraZoom {
+    const SAVE_KEY: &'static str = "camera_zoom";
+
+    fn load() -> Self {
+        persistence::load(Self::SAVE_KEY)
+            .map(Self)
+            .unwrap_or_default()
+    }
+}
+
+/// Whether two fingers are on the screen. The touch joystick is ignored while
+/// this is set, so that pinching doesn't also move the duck.
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Pinching(pub bool);
+
+fn zoom_with_mouse_wheel(
+    rig: Res<CameraRig>,
+    mut wheel: EventReader<MouseWheel>,
+    mut zoom: ResMut<CameraZoom>,
+) {
+    let scrolled: f32 = wheel
+        .read()
+        .map(|event| match event.unit {
+            MouseScrollUnit::Line => event.y * 0.1,
+            MouseScrollUnit::Pixel => event.y * 0.002,
+        })
+        .sum();
+    if scrolled != 0.0 {
+        // Scrolling up zooms in.
+        zoom.0 = (zoom.0 * (-scrolled).exp()).clamp(rig.min_zoom, rig.max_zoom);
+    }
+}
+
+fn zoom_with_pinch(
+    rig: Res<CameraRig>,
+    touches: Res<Touches>,
+    mut pinching: ResMut<Pinching>,
+    mut zoom: ResMut<CameraZoom>,
+) {
+    let fingers: Vec<_> = touches.iter().collect();
+    pinching.set_if_neq(Pinching(fingers.len() == 2));
+    let [a, b] = fingers[..] else {
+        return;
+    };
+
+    let distance = a.position().distance(b.position());
+    let previous_distance = a.previous_position().distance(b.previous_position());
+    if distance > 0.0 && previous_distance > 0.0 && distance != previous_distance {
+        // Spreading the fingers apart zooms in.
+        zoom.0 = (zoom.0 * previous_distance / distance).clamp(rig.min_zoom, rig.max_zoom);
+    }
+}
+
+/// Apply the [`CameraZoom`] to the camera, limited so that the view still fits
+/// inside the level. Runs every frame to also catch window resizes.
+///
+/// The zoom is clamped to the [`CameraRig`] limits here too, as the saved one
+/// may be from an edited config or older limits.
+fn apply_zoom(
+    rig: Res<CameraRig>,
+    zoom: Res<CameraZoom>,
+    bounds: Res<LevelBounds>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    mut projection_query: Query<&mut OrthographicProjection, With<Camera>>,
+) {
+    let Ok(window) = window_query.get_single() else {
+        return;
+    };
+    let zoom = zoom.0.clamp(rig.min_zoom, rig.max_zoom);
+    let fit = (bounds.0.size() / window.size()).min_element();
+    let scale = if fit.is_finite() { zoom.min(fit) } else { zoom };
+    for mut projection in &mut projection_query {
+        if projection.scale != scale {
+            projection.scale = scale;
+        }
+    }
+}
+
+/// Save the zoom level once the player stops zooming, instead of on every
+/// frame of a pinch.
+fn save_camera_zoom(zoom: Res<CameraZoom>, mut saved: Local<Option<f32>>) {
+    if zoom.is_changed() {
+        return;
+    }
+    match *saved {
+        // Nothing to save on startup, that's what we just loaded.
+        None => *saved = Some(zoom.0),
+        Some(previous) if previous != zoom.0 => {
+            persistence::save(CameraZoom::SAVE_KEY, &zoom.0);
+            *saved = Some(zoom.0);
+        }
+        Some(_) => {}
+    }
+}
+
+/// Center the camera on our duck as soon as it's spawned, which happens when
+/// the server tells us we joined.
+fn snap_to_joined_player(
+    bounds: Res<LevelBounds>,
+    player_query: Query<&Transform, (Added<Player>, Without<Camera>)>,
+    mut camera_query: Query<(&mut Transform, &OrthographicProjection), With<Camera>>,
+) {
+    let Ok(player) = player_query.get_single() else {
+        return;
+    };
+    for (mut camera, projection) in &mut camera_query {
+        let center = clamp_view_to_bounds(player.translation.truncate(), projection.area, bounds.0);
+        camera.translation = center.extend(camera.translation.z);
+    }
+}
+
+fn follow_player(
+    time: Res<Time>,
+    rig: Res<CameraRig>,
+    bounds: Res<LevelBounds>,
+    player_query: Query<(&Transform, &MovementController), (With<Player>, Without<Camera>)>,
+    mut camera_query: Query<(&mut Transform, &OrthographicProjection), With<Camera>>,
+) {
+    let focus = player_query
+        .get_single()
+        .ok()
+        .map(|(transform, controller)| {
+            transform.translation.truncate() + controller.velocity * rig.lookahead
+        });
+
+    for (mut camera, projection) in &mut camera_query {
+        let mut center = camera.translation.truncate();
+
+        if let Some(focus) = focus {
+            // Only follow by as much as the focus is outside the deadzone.
+            let offset = focus - center;
+            let excess = offset - offset.clamp(-rig.deadzone, rig.deadzone);
+            // Frame rate independent easing.
+            let t = 1.0 - (-rig.smoothing * time.delta_seconds()).exp();
+            center += excess * t;
+        }
+
+        let center = clamp_view_to_bounds(center, projection.area, bounds.0);
+        camera.translation = center.extend(camera.translation.z);
+    }
+}
+
+/// Move a camera centered at `center` so that its `view` (relative to the
+/// centre) stays inside `bounds`. Along an axis where the view is bigger than
+/// the level, the level is centered instead.
+fn clamp_view_to_bounds(center: Vec2, view: Rect, bounds: Rect) -> Vec2 {
+    let min = bounds.min - view.min;
+    let max = bounds.max - view.max;
+    let clamp_axis = |value: f32, min: f32, max: f32, middle: f32| {
+        if min > max {
+            middle
+        } else {
+            value.clamp(min, max)
+        }
+    };
+    Vec2::new(
+        clamp_axis(center.x, min.x, max.x, bounds.center().x),
+        clamp_axis(center.y, min.y, max.y, bounds.center().y),
+    )
+}
@@ -0,0 +1,671 @@
+//! Text chat.
+//!
+//! Press [`InputAction::Chat`] (or tap the field) to type, and Enter to send.
+//! Messages show up as a speech bubble above the sender's duck and in a
+//! scrollable log on the HUD. [`ChatSettings`] decides whether you see
+//! messages from everyone or only from ducks near you.
+
+use bevy::{
+    input::mouse::{MouseScrollUnit, MouseWheel},
+    prelude::*,
+    sprite::Anchor,
+    text::{Text2dBounds, TextLayoutInfo},
+    ui::FocusPolicy,
+};
+use bevy_simple_text_input::{
+    TextInputBundle, TextInputInactive, TextInputPlugin, TextInputSettings, TextInputSubmitEvent,
+    TextInputSystem, TextInputValue,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    input::input_map::{action_just_pressed, ActionInput, InputAction, KeyboardCaptured},
+    persistence,
+    screens::Screen,
+    theme::prelude::*,
+    AppSet,
+};
+
+use super::{
+    other_player::OtherPlayer, player::Player, websocket_chat_msg::ChatRequestEvent,
+    websocket_connect::OtherPlayerChattedWsReceived,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_plugins(TextInputPlugin);
+    app.insert_resource(ChatSettings::load());
+    app.add_event::<DuckChatted>();
+
+    app.add_systems(OnEnter(Screen::Gameplay), spawn_chat_hud);
+    app.add_systems(OnExit(Screen::Gameplay), release_keyboard);
+    app.add_systems(
+        Update,
+        (
+            tick_chat_bubbles.in_set(AppSet::TickTimers),
+            (
+                focus_chat_input.run_if(action_just_pressed(InputAction::Chat)),
+                focus_chat_input_on_click,
+                send_chat_messages,
+                unfocus_chat_input,
+                capture_keyboard,
+            )
+                .chain()
+                .after(TextInputSystem)
+                .in_set(ChatInputSet)
+                .in_set(AppSet::RecordInput),
+            (
+                other_player_chatted_handler,
+                add_chat_messages,
+                (fit_chat_bubbles, fade_chat_bubbles, scroll_chat_log),
+                update_chat_scope_button,
+            )
+                .chain()
+                .in_set(AppSet::Update),
+        )
+            .run_if(in_state(Screen::Gameplay)),
+    );
+    app.add_systems(
+        Update,
+        save_chat_settings.run_if(resource_changed::<ChatSettings>),
+    );
+}
+
+/// Focusing and unfocusing the chat input, which sets [`KeyboardCaptured`].
+/// Input read before this set on the same frame still sees the keyboard as
+/// captured if the chat input was focused.
+#[derive(SystemSet, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ChatInputSet;
+
+/// Longer messages are cut off.
+pub const MAX_CHAT_MESSAGE_CHARS: usize = 120;
+/// With [`ChatSettings::proximity_only`], messages from ducks further away
+/// than this are hidden.
+pub const CHAT_PROXIMITY_RADIUS: f32 = 700.0;
+/// Older lines are dropped from the log.
+const MAX_CHAT_LOG_LINES: usize = 50;
+const CHAT_BUBBLE_SECONDS: f32 = 6.0;
+const CHAT_BUBBLE_FADE_SECONDS: f32 = 1.5;
+/// In text pixels, before the bubble is scaled down to the duck.
+const CHAT_BUBBLE_WRAP_WIDTH: f32 = 400.0;
+const CHAT_BUBBLE_PADDING: f32 = 10.0;
+/// In the duck's local space, where the sprite is 32 units tall. Sits above
+/// the name label.
+const CHAT_BUBBLE_OFFSET: Vec2 = Vec2::new(0.0, 30.0);
+const CHAT_BUBBLE_BACKGROUND: Color = Color::srgba(1.0, 1.0, 1.0, 0.85);
+const CHAT_BUBBLE_TEXT: Color = Color::srgb(0.1, 0.1, 0.15);
+const CHAT_LOG_WIDTH: f32 = 360.0;
+const CHAT_LOG_HEIGHT: f32 = 140.0;
+
+/// Chat preferences, saved between sessions.
+#[derive(Resource, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChatSettings {
+    /// Only show messages from ducks within [`CHAT_PROXIMITY_RADIUS`].
+    pub proximity_only: bool,
+}
+
+impl Default for ChatSettings {
+    fn default() -> Self {
+        Self {
+            proximity_only: true,
+        }
+    }
+}
+
+impl ChatSettings {
+    const SAVE_KEY: &'static str = "chat_settings";
+
+    fn load() -> Self {
+        persistence::load(Self::SAVE_KEY).unwrap_or_default()
+    }
+}
+
+fn save_chat_settings(settings: Res<ChatSettings>) {
+    if settings.is_added() {
+        return;
+    }
+    persistence::save(ChatSettings::SAVE_KEY, &*settings);
+}
+
+/// A duck said something. Used for both your duck and other players' ducks.
+#[derive(Event, Debug, Clone)]
+pub struct DuckChatted {
+    pub duck: Entity,
+    /// Shown in the log, e.g. "You" or the other player's name.
+    pub sender: String,
+    pub message: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatResponseData {
+    player_uuid: String,
+    player_friendly_name: String,
+    message: String,
+}
+
+#[derive(Component)]
+struct ChatInput;
+
+/// The clipped part of the log, which you can scroll over.
+#[derive(Component)]
+pub struct ChatLogViewport;
+
+/// The log lines, moved up and down inside the [`ChatLogViewport`].
+#[derive(Component)]
+struct ChatLogList {
+    offset: f32,
+    /// Keep showing the newest line, unless you scrolled up to read older ones.
+    stick_to_bottom: bool,
+}
+
+#[derive(Component)]
+struct ChatLogLine;
+
+#[derive(Component)]
+struct ChatScopeButton;
+
+#[derive(Component)]
+struct ChatBubble(Timer);
+
+#[derive(Component)]
+struct ChatBubbleText;
+
+fn spawn_chat_hud(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let font = asset_server.load("FiraSans-Bold.ttf");
+
+    commands
+        .spawn((
+            Name::new("Chat"),
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    left: Val::Percent(50.),
+                    bottom: Val::Percent(3.),
+                    width: Val::Px(CHAT_LOG_WIDTH),
+                    margin: UiRect::left(Val::Px(-CHAT_LOG_WIDTH / 2.0)),
+                    flex_direction: FlexDirection::Column,
+                    row_gap: Val::Px(4.0),
+                    ..default()
+                },
+                ..default()
+            },
+            StateScoped(Screen::Gameplay),
+        ))
+        .with_children(|chat| {
+            chat.spawn(NodeBundle {
+                style: Style {
+                    justify_content: JustifyContent::End,
+                    ..default()
+                },
+                ..default()
+            })
+            .with_children(|header| {
+                header
+                    .small_button("")
+                    .insert((
+                        ChatScopeButton,
+                        Style {
+                            width: Val::Px(170.0),
+                            height: Val::Px(28.0),
+                            justify_content: JustifyContent::Center,
+                            align_items: AlignItems::Center,
+                            ..default()
+                        },
+                    ))
+                    .observe(toggle_chat_scope);
+            });
+
+            chat.spawn((
+                Name::new("Chat log"),
+                ChatLogViewport,
+                NodeBundle {
+                    style: Style {
+                        height: Val::Px(CHAT_LOG_HEIGHT),
+                        flex_direction: FlexDirection::Column,
+                        padding: UiRect::all(Val::Px(6.0)),
+                        overflow: Overflow::clip_y(),
+                        ..default()
+                    },
+                    background_color: Color::srgba(0.0, 0.0, 0.0, 0.35).into(),
+                    focus_policy: FocusPolicy::Block,
+                    ..default()
+                },
+                Interaction::default(),
+            ))
+            .with_children(|viewport| {
+                viewport.spawn((
+                    ChatLogList {
+                        offset: 0.0,
+                        stick_to_bottom: true,
+                    },
+                    NodeBundle {
+                        style: Style {
+                            flex_direction: FlexDirection::Column,
+                            // Grow past the viewport so that there's something to scroll.
+                            flex_shrink: 0.0,
+                            row_gap: Val::Px(2.0),
+                            ..default()
+                        },
+                        ..default()
+                    },
+                ));
+            });
+
+            chat.spawn((
+                Name::new("Chat input"),
+                ChatInput,
+                NodeBundle {
+                    style: Style {
+                        padding: UiRect::all(Val::Px(6.0)),
+                        border: UiRect::all(Val::Px(2.0)),
+                        ..default()
+                    },
+                    background_color: Color::srgba(0.0, 0.0, 0.0, 0.5).into(),
+                    border_color: ui_palette::NODE_BACKGROUND.into(),
+                    focus_policy: FocusPolicy::Block,
+                    ..default()
+                },
+                TextInputBundle::default()
+                    .with_text_style(TextStyle {
+                        font: font.clone(),
+                        font_size: 18.0,
+                        color: Color::WHITE,
+                    })
+                    .with_placeholder(
+                        "Press Enter to chat",
+                        Some(TextStyle {
+                            font,
+                            font_size: 18.0,
+                            color: Color::srgba(1.0, 1.0, 1.0, 0.5),
+                        }),
+                    )
+                    .with_settings(TextInputSettings {
+                        retain_on_submit: false,
+                        mask_character: None,
+                    })
+                    .with_inactive(true),
+            ));
+        });
+}
+
+fn set_chat_input_active(active: bool, input: &mut TextInputInactive, border: &mut BorderColor) {
+    input.0 = !active;
+    *border = if active {
+        ui_palette::BUTTON_HOVERED_BACKGROUND.into()
+    } else {
+        ui_palette::NODE_BACKGROUND.into()
+    };
+}
+
+fn focus_chat_input(
+    mut input_query: Query<(&mut TextInputInactive, &mut BorderColor), With<ChatInput>>,
+) {
+    for (mut input, mut border) in &mut input_query {
+        set_chat_input_active(true, &mut input, &mut border);
+    }
+}
+
+fn focus_chat_input_on_click(
+    mut input_query: Query<
+        (&Interaction, &mut TextInputInactive, &mut BorderColor),
+        (With<ChatInput>, Changed<Interaction>),
+    >,
+) {
+    for (interaction, mut input, mut border) in &mut input_query {
+        if *interaction == Interaction::Pressed {
+            set_chat_input_active(true, &mut input, &mut border);
+        }
+    }
+}
+
+/// Stop typing on [`InputAction::Pause`], or when clicking or tapping
+/// anywhere else.
+fn unfocus_chat_input(
+    action_input: ActionInput,
+    mouse: Res<ButtonInput<MouseButton>>,
+    touches: Res<Touches>,
+    mut input_query: Query<
+        (
+            &Interaction,
+            &mut TextInputInactive,
+            &mut TextInputValue,
+            &mut BorderColor,
+        ),
+        With<ChatInput>,
+    >,
+) {
+    let clicked = mouse.just_pressed(MouseButton::Left) || touches.any_just_pressed();
+    for (interaction, mut input, mut value, mut border) in &mut input_query {
+        if input.0 {
+            continue;
+        }
+        if action_input.just_typed(InputAction::Pause) {
+            value.0.clear();
+            set_chat_input_active(false, &mut input, &mut border);
+        } else if clicked && *interaction != Interaction::Pressed {
+            set_chat_input_active(false, &mut input, &mut border);
+        }
+    }
+}
+
+fn capture_keyboard(
+    input_query: Query<&TextInputInactive, With<ChatInput>>,
+    mut captured: ResMut<KeyboardCaptured>,
+) {
+    let typing = input_query.iter().any(|input| !input.0);
+    captured.set_if_neq(KeyboardCaptured(typing));
+}
+
+fn release_keyboard(mut captured: ResMut<KeyboardCaptured>) {
+    captured.set_if_neq(KeyboardCaptured(false));
+}
+
+fn send_chat_messages(
+    mut submit_events: EventReader<TextInputSubmitEvent>,
+    mut input_query: Query<(&mut TextInputInactive, &mut BorderColor), With<ChatInput>>,
+    player_query: Query<Entity, With<Player>>,
+    mut chat_request_event_writer: EventWriter<ChatRequestEvent>,
+    mut duck_chatted_event_writer: EventWriter<DuckChatted>,
+) {
+    for event in submit_events.read() {
+        let Ok((mut input, mut border)) = input_query.get_mut(event.entity) else {
+            continue;
+        };
+        // Enter sends the message and stops typing, so you can move again.
+        set_chat_input_active(false, &mut input, &mut border);
+
+        let message: String = event
+            .value
+            .trim()
+            .chars()
+            .take(MAX_CHAT_MESSAGE_CHARS)
+            .collect();
+        if message.is_empty() {
+            continue;
+        }
+
+        chat_request_event_writer.send(ChatRequestEvent {
+            message: message.clone(),
+        });
+        for duck in &player_query {
+            duck_chatted_event_writer.send(DuckChatted {
+                duck,
+                sender: "You".to_string(),
+                message: message.clone(),
+            });
+        }
+    }
+}
+
+fn other_player_chatted_handler(
+    mut event_reader: EventReader<OtherPlayerChattedWsReceived>,
+    settings: Res<ChatSettings>,
+    player_query: Query<&GlobalTransform, With<Player>>,
+    other_players: Query<(Entity, &Name, &GlobalTransform), With<OtherPlayer>>,
+    mut duck_chatted_event_writer: EventWriter<DuckChatted>,
+) {
+    for e in event_reader.read() {
+        let data: ChatResponseData = match serde_json::from_value(e.data.clone()) {
+            Ok(data) => data,
+            Err(op) => {
+                info!("Failed to parse incoming websocket message: {}", op);
+                continue;
+            }
+        };
+
+        let Some((duck, _, duck_transform)) = other_players
+            .iter()
+            .find(|(_, name, _)| name.as_str() == data.player_uuid)
+        else {
+            info!(
+                "Got a chat message from unknown player {}",
+                data.player_uuid
+            );
+            continue;
+        };
+
+        if settings.proximity_only {
+            let Ok(player_transform) = player_query.get_single() else {
+                continue;
+            };
+            let distance = player_transform
+                .translation()
+                .truncate()
+                .distance(duck_transform.translation().truncate());
+            if distance > CHAT_PROXIMITY_RADIUS {
+                continue;
+            }
+        }
+
+        duck_chatted_event_writer.send(DuckChatted {
+            duck,
+            sender: data.player_friendly_name,
+            message: data.message.chars().take(MAX_CHAT_MESSAGE_CHARS).collect(),
+        });
+    }
+}
+
+fn add_chat_messages(
+    mut commands: Commands,
+    mut events: EventReader<DuckChatted>,
+    asset_server: Res<AssetServer>,
+    children_query: Query<&Children>,
+    bubble_query: Query<(), With<ChatBubble>>,
+    list_query: Query<Entity, With<ChatLogList>>,
+    line_query: Query<(), With<ChatLogLine>>,
+) {
+    let font = asset_server.load("FiraSans-Bold.ttf");
+
+    for event in events.read() {
+        // Saying something new replaces the old bubble instead of stacking them.
+        for child in children_query.get(event.duck).into_iter().flatten() {
+            if bubble_query.contains(*child) {
+                commands.entity(*child).despawn_recursive();
+            }
+        }
+
+        if let Some(mut duck) = commands.get_entity(event.duck) {
+            duck.with_children(|parent| {
+                parent
+                    .spawn((
+                        Name::new("Chat bubble"),
+                        ChatBubble(Timer::from_seconds(CHAT_BUBBLE_SECONDS, TimerMode::Once)),
+                        SpriteBundle {
+                            sprite: Sprite {
+                                color: CHAT_BUBBLE_BACKGROUND,
+                                anchor: Anchor::BottomCenter,
+                                // Sized to the text by `fit_chat_bubbles`.
+                                custom_size: Some(Vec2::ZERO),
+                                ..default()
+                            },
+                            transform: Transform {
+                                translation: CHAT_BUBBLE_OFFSET.extend(3.0),
+                                // Cancel out the duck's 4x scale, like the name label.
+                                scale: Vec3::new(0.25, 0.25, 1.0),
+                                ..default()
+                            },
+                            ..default()
+                        },
+                    ))
+                    .with_children(|bubble| {
+                        bubble.spawn((
+                            ChatBubbleText,
+                            Text2dBundle {
+                                text: Text::from_section(
+                                    event.message.clone(),
+                                    TextStyle {
+                                        font: font.clone(),
+                                        font_size: 22.0,
+                                        color: CHAT_BUBBLE_TEXT,
+                                    },
+                                )
+                                .with_justify(JustifyText::Center),
+                                text_anchor: Anchor::BottomCenter,
+                                text_2d_bounds: Text2dBounds {
+                                    size: Vec2::new(CHAT_BUBBLE_WRAP_WIDTH, f32::INFINITY),
+                                },
+                                transform: Transform::from_xyz(0.0, CHAT_BUBBLE_PADDING, 0.1),
+                                ..default()
+                            },
+                        ));
+                    });
+            });
+        }
+
+        let Ok(list) = list_query.get_single() else {
+            continue;
+        };
+        if let Ok(children) = children_query.get(list) {
+            let lines: Vec<_> = children
+                .iter()
+                .filter(|child| line_query.contains(**child))
+                .collect();
+            let excess = (lines.len() + 1).saturating_sub(MAX_CHAT_LOG_LINES);
+            for old_line in &lines[..excess] {
+                commands.entity(**old_line).despawn_recursive();
+            }
+        }
+        commands.entity(list).with_children(|list| {
+            list.spawn((
+                ChatLogLine,
+                TextBundle::from_sections([
+                    TextSection::new(
+                        format!("{}: ", event.sender),
+                        TextStyle {
+                            font: font.clone(),
+                            font_size: 16.0,
+                            color: Color::srgb(1.0, 0.9, 0.4),
+                        },
+                    ),
+                    TextSection::new(
+                        event.message.clone(),
+                        TextStyle {
+                            font: font.clone(),
+                            font_size: 16.0,
+                            color: Color::WHITE,
+                        },
+                    ),
+                ]),
+            ));
+        });
+    }
+}
+
+fn tick_chat_bubbles(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut bubble_query: Query<(Entity, &mut ChatBubble)>,
+) {
+    for (entity, mut bubble) in &mut bubble_query {
+        bubble.0.tick(time.delta());
+        if bubble.0.finished() {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+/// Size the bubble's background to its wrapped text.
+fn fit_chat_bubbles(
+    mut bubble_query: Query<(&mut Sprite, &Children), With<ChatBubble>>,
+    text_query: Query<&TextLayoutInfo, (With<ChatBubbleText>, Changed<TextLayoutInfo>)>,
+) {
+    for (mut sprite, children) in &mut bubble_query {
+        for child in children {
+            if let Ok(layout) = text_query.get(*child) {
+                sprite.custom_size = Some(layout.logical_size + 2.0 * CHAT_BUBBLE_PADDING);
+            }
+        }
+    }
+}
+
+fn fade_chat_bubbles(
+    mut bubble_query: Query<(&ChatBubble, &mut Sprite, &Children)>,
+    mut text_query: Query<&mut Text, With<ChatBubbleText>>,
+) {
+    for (bubble, mut sprite, children) in &mut bubble_query {
+        let alpha = (bubble.0.remaining_secs() / CHAT_BUBBLE_FADE_SECONDS).min(1.0);
+        sprite.color = CHAT_BUBBLE_BACKGROUND.with_alpha(CHAT_BUBBLE_BACKGROUND.alpha() * alpha);
+        for child in children {
+            if let Ok(mut text) = text_query.get_mut(*child) {
+                for section in &mut text.sections {
+                    section.style.color = CHAT_BUBBLE_TEXT.with_alpha(alpha);
+                }
+            }
+        }
+    }
+}
+
+/// Scroll the log with the mouse wheel or by dragging it.
+fn scroll_chat_log(
+    mut wheel: EventReader<MouseWheel>,
+    touches: Res<Touches>,
+    viewport_query: Query<(&Node, &Interaction), With<ChatLogViewport>>,
+    mut list_query: Query<(&mut ChatLogList, &mut Style, &Node, &Parent)>,
+) {
+    let wheel_delta: f32 = wheel
+        .read()
+        .map(|event| match event.unit {
+            MouseScrollUnit::Line => event.y * 20.0,
+            MouseScrollUnit::Pixel => event.y,
+        })
+        .sum();
+    let drag_delta: f32 = touches.iter().map(|touch| touch.delta().y).sum();
+
+    for (mut list, mut style, list_node, parent) in &mut list_query {
+        let Ok((viewport_node, interaction)) = viewport_query.get(parent.get()) else {
+            continue;
+        };
+        let delta = match interaction {
+            Interaction::Hovered => wheel_delta,
+            Interaction::Pressed => drag_delta,
+            Interaction::None => 0.0,
+        };
+
+        // The viewport's padding is part of its size, but not room for lines.
+        let max_scroll = (list_node.size().y - viewport_node.size().y + 12.0).max(0.0);
+        if delta != 0.0 {
+            list.offset = (list.offset + delta).clamp(-max_scroll, 0.0);
+            list.stick_to_bottom = list.offset <= -max_scroll + 1.0;
+        }
+        if list.stick_to_bottom {
+            list.offset = -max_scroll;
+        }
+
+        let top = Val::Px(list.offset);
+        if style.top != top {
+            style.top = top;
+        }
+    }
+}
+
+/// Whether the pointer is over the chat log, so the mouse wheel scrolls it
+/// instead of zooming.
+pub fn chat_log_hovered(viewport_query: Query<&Interaction, With<ChatLogViewport>>) -> bool {
+    viewport_query
+        .iter()
+        .any(|interaction| *interaction != Interaction::None)
+}
+
+fn toggle_chat_scope(_trigger: Trigger<OnPress>, mut settings: ResMut<ChatSettings>) {
+    settings.proximity_only = !settings.proximity_only;
+}
+
+fn update_chat_scope_button(
+    settings: Res<ChatSettings>,
+    button_query: Query<&Children, With<ChatScopeButton>>,
+    mut text_query: Query<&mut Text>,
+) {
+    let label = if settings.proximity_only {
+        "Chat: Nearby"
+    } else {
+        "Chat: Everyone"
+    };
+    for children in &button_query {
+        for child in children {
+            if let Ok(mut text) = text_query.get_mut(*child) {
+                if text.sections[0].value != label {
+                    text.sections[0].value = label.to_string();
+                }
+            }
+        }
+    }
+}
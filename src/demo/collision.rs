@@ -0,0 +1,231 @@
+//! Static obstacle collision.
+//!
+//! Level props can carry a [`Collider`]. When the level is spawned, all of them
+//! are gathered into the [`CollisionLayer`], which the local duck's movement is
+//! resolved against. Overlaps are pushed back out along the contact normal,
+//! which keeps any sideways movement, so ducks slide along walls instead of
+//! sticking to them.
+//!
+//! [`Collider`] and [`CollisionLayer`] serialize to plain JSON (see
+//! [`CollisionLayer::to_json`]), so the server can load the exact same shapes
+//! and validate moves against them.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<Collider>();
+    app.init_resource::<CollisionLayer>();
+}
+
+/// A collision shape, centered on its entity.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Serialize, Deserialize, Reflect)]
+#[reflect(Component)]
+pub enum Collider {
+    /// An axis-aligned box.
+    Aabb {
+        half_size: Vec2,
+    },
+    Circle {
+        radius: f32,
+    },
+}
+
+impl Collider {
+    /// How far a circle at `center` has to move to stop overlapping this
+    /// collider placed at `position`, or `None` if they don't overlap.
+    fn push_out_circle(&self, position: Vec2, center: Vec2, radius: f32) -> Option<Vec2> {
+        match *self {
+            Collider::Circle { radius: own_radius } => {
+                let offset = center - position;
+                let distance = offset.length();
+                let overlap = own_radius + radius - distance;
+                if overlap <= 0.0 {
+                    return None;
+                }
+                let normal = offset.try_normalize().unwrap_or(Vec2::Y);
+                Some(normal * overlap)
+            }
+            Collider::Aabb { half_size } => {
+                let min = position - half_size;
+                let max = position + half_size;
+                let closest = center.clamp(min, max);
+                let offset = center - closest;
+                let distance = offset.length();
+
+                if distance > 0.0 {
+                    let overlap = radius - distance;
+                    return (overlap > 0.0).then(|| offset / distance * overlap);
+                }
+
+                // The circle's center is inside the box, so push it out
+                // through the nearest side.
+                let to_min = center - min;
+                let to_max = max - center;
+                let exits = [
+                    Vec2::new(-(to_min.x + radius), 0.0),
+                    Vec2::new(to_max.x + radius, 0.0),
+                    Vec2::new(0.0, -(to_min.y + radius)),
+                    Vec2::new(0.0, to_max.y + radius),
+                ];
+                exits
+                    .into_iter()
+                    .min_by(|a, b| a.length_squared().total_cmp(&b.length_squared()))
+            }
+        }
+    }
+}
+
+/// A [`Collider`] at a fixed position in the world.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PlacedCollider {
+    pub position: Vec2,
+    pub collider: Collider,
+}
+
+/// All static colliders of the current level.
+#[derive(Resource, Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CollisionLayer {
+    pub colliders: Vec<PlacedCollider>,
+}
+
+impl CollisionLayer {
+    /// Resolving one overlap can push into another collider, so repeat a
+    /// few times to settle in corners.
+    const MAX_ITERATIONS: usize = 4;
+
+    /// Move a circle at `center` out of every collider it overlaps.
+    pub fn resolve_circle(&self, mut center: Vec2, radius: f32) -> Vec2 {
+        for _ in 0..Self::MAX_ITERATIONS {
+            let mut resolved = true;
+            for placed in &self.colliders {
+                if let Some(push) = placed
+                    .collider
+                    .push_out_circle(placed.position, center, radius)
+                {
+                    center += push;
+                    resolved = false;
+                }
+            }
+            if resolved {
+                break;
+            }
+        }
+        center
+    }
+
+    /// The JSON shared with the server, e.g.
+    /// `{"colliders":[{"position":[300.0,200.0],"collider":{"Circle":{"radius":40.0}}}]}`.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap_or_else(|err| {
+            warn!("Couldn't serialize the collision layer: {}", err);
+            String::new()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn layer(colliders: impl IntoIterator<Item = (Vec2, Collider)>) -> CollisionLayer {
+        CollisionLayer {
+            colliders: colliders
+                .into_iter()
+                .map(|(position, collider)| PlacedCollider { position, collider })
+                .collect(),
+        }
+    }
+
+    fn wall() -> CollisionLayer {
+        layer([(
+            Vec2::ZERO,
+            Collider::Aabb {
+                half_size: Vec2::new(100.0, 50.0),
+            },
+        )])
+    }
+
+    fn assert_near(actual: Vec2, expected: Vec2) {
+        assert!(
+            actual.abs_diff_eq(expected, 1e-4),
+            "{actual} is not {expected}"
+        );
+    }
+
+    #[test]
+    fn circle_clear_of_everything_stays_put() {
+        assert_near(
+            wall().resolve_circle(Vec2::new(0.0, 80.0), 10.0),
+            Vec2::new(0.0, 80.0),
+        );
+    }
+
+    #[test]
+    fn circle_slides_along_a_box_face() {
+        // Only the part of the move into the face is undone.
+        assert_near(
+            wall().resolve_circle(Vec2::new(30.0, 55.0), 10.0),
+            Vec2::new(30.0, 60.0),
+        );
+        assert_near(
+            wall().resolve_circle(Vec2::new(-105.0, 20.0), 10.0),
+            Vec2::new(-110.0, 20.0),
+        );
+    }
+
+    #[test]
+    fn circle_is_pushed_away_from_a_box_corner() {
+        let resolved = wall().resolve_circle(Vec2::new(105.0, 55.0), 10.0);
+        let corner = Vec2::new(100.0, 50.0);
+        assert!((resolved.distance(corner) - 10.0).abs() < 1e-4);
+        assert_near((resolved - corner).normalize(), Vec2::ONE.normalize());
+    }
+
+    #[test]
+    fn circle_inside_a_box_leaves_through_the_nearest_side() {
+        assert_near(
+            wall().resolve_circle(Vec2::new(0.0, 45.0), 10.0),
+            Vec2::new(0.0, 60.0),
+        );
+    }
+
+    #[test]
+    fn circle_is_pushed_out_of_a_circle() {
+        let layer = layer([(Vec2::ZERO, Collider::Circle { radius: 20.0 })]);
+        assert_near(
+            layer.resolve_circle(Vec2::new(25.0, 0.0), 10.0),
+            Vec2::new(30.0, 0.0),
+        );
+        assert_near(
+            layer.resolve_circle(Vec2::new(0.0, -28.0), 10.0),
+            Vec2::new(0.0, -30.0),
+        );
+        assert_near(
+            layer.resolve_circle(Vec2::new(40.0, 0.0), 10.0),
+            Vec2::new(40.0, 0.0),
+        );
+    }
+
+    #[test]
+    fn circle_settles_in_a_corner_between_two_boxes() {
+        let layer = layer([
+            (
+                Vec2::new(0.0, -60.0),
+                Collider::Aabb {
+                    half_size: Vec2::new(100.0, 10.0),
+                },
+            ),
+            (
+                Vec2::new(-60.0, 0.0),
+                Collider::Aabb {
+                    half_size: Vec2::new(10.0, 100.0),
+                },
+            ),
+        ]);
+        assert_near(
+            layer.resolve_circle(Vec2::new(-45.0, -45.0), 10.0),
+            Vec2::new(-40.0, -40.0),
+        );
+    }
+}
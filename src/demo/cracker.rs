@@ -1,13 +1,134 @@
-use bevy::{prelude::*, utils::info};
+use bevy::prelude::*;
+use serde::Deserialize;
 
-use super::websocket_connect::MoveCrackersBevyEvent;
+use crate::{
+    audio::{AudioChannel, PlaySfx},
+    input::input_map::{action_just_pressed, InputAction},
+    screens::Screen,
+    AppSet,
+};
 
-#[derive(Component)]
-struct _CrackerComponent;
+use super::{
+    buffs::Buff,
+    death::player_alive,
+    minimap::MinimapIcon,
+    player::Player,
+    websocket_connect::{
+        DespawnCrackerBevyEvent, MoveCrackersBevyEvent, SpawnCrackerBevyEvent,
+        UpdateCrackerBevyEvent,
+    },
+    websocket_interact_msg::InteractRequestEvent,
+};
+
+/// A cracker, keyed by the id the server gave it.
+#[derive(Component, Debug, Clone, PartialEq, Eq, Reflect)]
+#[reflect(Component)]
+pub struct Cracker {
+    pub id: String,
+    pub kind: CrackerKind,
+}
+
+/// What kind of cracker it is. Everything but regular crackers gives a
+/// [`Buff`] to whoever eats it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Deserialize, Reflect)]
+#[serde(rename_all = "snake_case")]
+pub enum CrackerKind {
+    #[default]
+    Regular,
+    Golden,
+    SpeedSeed,
+    MagnetBread,
+}
+
+impl CrackerKind {
+    /// There are no sprites per kind yet, so all kinds share the cracker
+    /// sprite, tinted per kind. Tints are hard to tell apart at night, so
+    /// kinds also differ in size and have a badge next to their points.
+    pub fn tint(self) -> Color {
+        match self {
+            Self::Regular => Color::WHITE,
+            Self::Golden => Color::srgb(1.0, 0.84, 0.2),
+            Self::SpeedSeed => Color::srgb(0.45, 1.0, 0.45),
+            Self::MagnetBread => Color::srgb(0.85, 0.45, 1.0),
+        }
+    }
+
+    fn size(self) -> Vec2 {
+        match self {
+            Self::Regular => CRACKER_SIZE,
+            Self::Golden => CRACKER_SIZE * 1.3,
+            Self::SpeedSeed => CRACKER_SIZE * 0.7,
+            Self::MagnetBread => CRACKER_SIZE * 1.15,
+        }
+    }
+
+    /// Shown before the points, so a kind can be told apart without its tint.
+    fn badge(self) -> &'static str {
+        match self {
+            Self::Regular => "",
+            Self::Golden => "Gold ",
+            Self::SpeedSeed => "Speed ",
+            Self::MagnetBread => "Magnet ",
+        }
+    }
 
+    /// Playback speed of the eating sound, so each kind sounds a bit different.
+    fn pitch(self) -> f32 {
+        match self {
+            Self::Regular => 1.0,
+            Self::Golden => 1.25,
+            Self::SpeedSeed => 1.6,
+            Self::MagnetBread => 0.8,
+        }
+    }
+
+    pub fn buff(self) -> Option<Buff> {
+        match self {
+            Self::Regular | Self::Golden => None,
+            Self::SpeedSeed => Some(Buff::Speed),
+            Self::MagnetBread => Some(Buff::Magnet),
+        }
+    }
+}
+
+/// How close a cracker has to be for us to try to pick it up.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Reflect)]
+#[reflect(Component)]
+pub struct PickupRadius(pub f32);
+
+pub const BASE_PICKUP_RADIUS: f32 = 50.0;
+
+/// [`InputAction::Interact`] reaches this many times further than the
+/// [`PickupRadius`], for a cracker that's awkward to walk over.
+const INTERACT_REACH_FACTOR: f32 = 2.0;
+
+/// Don't ask the server for the same cracker again until this many seconds
+/// have passed, it may just not have answered yet.
+const PICKUP_RETRY_SECONDS: f32 = 0.5;
+
+/// The point value and kind badge shown above a [`Cracker`].
 #[derive(Component)]
-struct _CrackerText;
+struct CrackerText;
+
+/// A cracker as the server describes it, e.g. in the `YouJoined` snapshot.
+#[derive(Debug, Clone, Deserialize)]
+pub struct CrackerData {
+    pub cracker_id: String,
+    pub x_position: f32,
+    pub y_position: f32,
+    pub points: u64,
+    #[serde(default)]
+    pub kind: CrackerKind,
+}
 
+/// The text above a cracker: its points, and its kind if it's not regular.
+fn cracker_label(data: &CrackerData) -> String {
+    format!("{}{}", data.kind.badge(), data.points)
+}
+
+/// `images/cracker-v1.png` is 512x256.
+const CRACKER_SIZE: Vec2 = Vec2::new(38.4, 19.2);
+const CRACKER_Z: f32 = 5.;
 const CRACKER_TEXT_OFFSET: f32 = 22.;
 
 #[derive(Resource)]
@@ -19,12 +140,31 @@ pub struct MoveCrackerSoundFx {
     pub sound_handle: Handle<AudioSource>,
 }
 
-pub(super) fn plugin(_app: &mut App) {
-    // WIP - cracker stuff
-    _app.add_systems(Startup, _create_cracker);
-    _app.add_systems(Startup, _create_cracker_text);
-    _app.add_systems(Startup, cracker_sounds_setup);
-    _app.add_systems(Update, listen_for_move_cracker_bevy_event);
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<Cracker>();
+    app.register_type::<PickupRadius>();
+
+    app.add_systems(Startup, cracker_sounds_setup);
+    app.add_systems(
+        Update,
+        (
+            listen_for_spawn_cracker_bevy_event,
+            listen_for_update_cracker_bevy_event,
+            listen_for_move_cracker_bevy_event,
+            listen_for_despawn_cracker_bevy_event,
+        )
+            .chain(),
+    );
+    app.add_systems(
+        Update,
+        (
+            pick_up_nearest_cracker
+                .run_if(action_just_pressed(InputAction::Interact))
+                .in_set(AppSet::RecordInput),
+            request_pickups_in_range.in_set(AppSet::Update),
+        )
+            .run_if(in_state(Screen::Gameplay).and_then(player_alive)),
+    );
 }
 
 fn cracker_sounds_setup(mut commands: Commands, asset_server: Res<AssetServer>) {
@@ -36,91 +176,219 @@ fn cracker_sounds_setup(mut commands: Commands, asset_server: Res<AssetServer>)
     commands.insert_resource(MoveCrackerSoundFx { sound_handle: cracker_move_sound_handle });
 }
 
+fn listen_for_spawn_cracker_bevy_event(
+    mut commands: Commands,
+    mut event_reader: EventReader<SpawnCrackerBevyEvent>,
+    mut cracker_query: Query<(&mut Cracker, &mut Transform, &mut Sprite, &Children)>,
+    mut text_query: Query<&mut Text, With<CrackerText>>,
+    asset_server: Res<AssetServer>,
+) {
+    for e in event_reader.read() {
+        info!("Spawning cracker {}", e.data.cracker_id);
+
+        // The server may resend a cracker we already have, e.g. after reconnecting.
+        if !set_cracker(&e.data, &mut cracker_query, &mut text_query) {
+            spawn_cracker(&mut commands, &asset_server, &e.data);
+        }
+    }
+}
+
+fn listen_for_update_cracker_bevy_event(
+    mut commands: Commands,
+    mut event_reader: EventReader<UpdateCrackerBevyEvent>,
+    mut cracker_query: Query<(&mut Cracker, &mut Transform, &mut Sprite, &Children)>,
+    mut text_query: Query<&mut Text, With<CrackerText>>,
+    asset_server: Res<AssetServer>,
+) {
+    for e in event_reader.read() {
+        if !set_cracker(&e.data, &mut cracker_query, &mut text_query) {
+            warn!("Got an update for unknown cracker {}, spawning it", e.data.cracker_id);
+            spawn_cracker(&mut commands, &asset_server, &e.data);
+        }
+    }
+}
+
 fn listen_for_move_cracker_bevy_event(
     mut commands: Commands,
     mut bevy_move_crackers_event_reader: EventReader<MoveCrackersBevyEvent>,
-    mut param_set: ParamSet<(
-        Query<&mut Transform, With<_CrackerComponent>>,
-        Query<&mut Transform, With<_CrackerText>>,
-        Query<&mut Text, With<_CrackerText>>,
-    )>,
+    mut cracker_query: Query<(&mut Cracker, &mut Transform, &mut Sprite, &Children)>,
+    mut text_query: Query<&mut Text, With<CrackerText>>,
+    asset_server: Res<AssetServer>,
     audio: Res<MoveCrackerSoundFx>,
-    audio_assets: Res<Assets<AudioSource>>,
+    mut play_sfx: EventWriter<PlaySfx>,
 ) {
     for e in bevy_move_crackers_event_reader.read() {
-
         info!(
-            "Moving crackers! x: {:?}, y: {:?}",
-            e.x_position, e.y_position
+            "Moving cracker {}! x: {:?}, y: {:?}",
+            e.cracker_id, e.x_position, e.y_position
         );
 
-        for mut transform in param_set.p0().iter_mut() {
-            // Mutate the transform, e.g., moving the cracker component upwards
-            transform.translation.x = e.x_position;
-            transform.translation.y = e.y_position;
-        }
+        // Where it was eaten, which is where the duck that ate it is.
+        let eaten_at = cracker_query
+            .iter()
+            .find(|(cracker, _, _, _)| cracker.id == e.cracker_id)
+            .map(|(_, transform, _, _)| transform.translation.truncate());
 
-        for mut transform in param_set.p1().iter_mut() {
-            // Mutate the transform, e.g., moving the cracker component upwards
-            transform.translation.x = e.x_position;
-            transform.translation.y = e.y_position + CRACKER_TEXT_OFFSET;
+        let data = CrackerData {
+            cracker_id: e.cracker_id.clone(),
+            x_position: e.x_position,
+            y_position: e.y_position,
+            points: e.points,
+            kind: e.kind,
+        };
+        if !set_cracker(&data, &mut cracker_query, &mut text_query) {
+            spawn_cracker(&mut commands, &asset_server, &data);
         }
 
-        for mut transform in param_set.p2().iter_mut() {
-            transform.sections[0].value = e.points.to_string();
+        // Play chewing sound effect, from the other duck if it wasn't you.
+        let chew = |channel| {
+            PlaySfx::new(channel, audio.sound_handle.clone()).with_speed(e.eaten_kind.pitch())
+        };
+        if e.you_got_crackers {
+            play_sfx.send(chew(AudioChannel::Sfx));
+        } else if let Some(eaten_at) = eaten_at {
+            play_sfx.send(chew(AudioChannel::RemotePlayers).at(eaten_at));
         }
+    }
+}
 
-        // Play chewing sound effect
-        if let Some(_) = audio_assets.get(&audio.sound_handle) {
-            // Spawn an audio source to play the sound
-            commands.spawn(AudioSourceBundle {
-                source: audio.sound_handle.clone(), // Clone the handle to use it
-                ..Default::default()                // Use default values for other fields
-            });
-            println!("Playing cracker moved sound.");
-        } else {
-            println!("Audio not loaded yet.");
+fn listen_for_despawn_cracker_bevy_event(
+    mut commands: Commands,
+    mut event_reader: EventReader<DespawnCrackerBevyEvent>,
+    cracker_query: Query<(Entity, &Cracker)>,
+) {
+    for e in event_reader.read() {
+        info!("Despawning cracker {}", e.cracker_id);
+
+        for (entity, cracker) in &cracker_query {
+            if cracker.id == e.cracker_id {
+                commands.entity(entity).despawn_recursive();
+            }
         }
     }
 }
 
-fn _create_cracker(mut commands: Commands, asset_server: Res<AssetServer>) {
-    let texture_handle = asset_server.load("images/cracker-v1.png");
+/// Move an existing cracker and update its points. Returns `false` if there's
+/// no cracker with that id yet.
+fn set_cracker(
+    data: &CrackerData,
+    cracker_query: &mut Query<(&mut Cracker, &mut Transform, &mut Sprite, &Children)>,
+    text_query: &mut Query<&mut Text, With<CrackerText>>,
+) -> bool {
+    let Some((mut cracker, mut transform, mut sprite, children)) = cracker_query
+        .iter_mut()
+        .find(|(cracker, _, _, _)| cracker.id == data.cracker_id)
+    else {
+        return false;
+    };
 
-    println!("creating cracker...");
+    transform.translation.x = data.x_position;
+    transform.translation.y = data.y_position;
+    cracker.kind = data.kind;
+    sprite.color = data.kind.tint();
+    sprite.custom_size = Some(data.kind.size());
 
-    commands
-        .spawn(SpriteBundle {
-            texture: texture_handle,
-            transform: Transform {
-                scale: Vec3::new(0.075, 0.075, 0.0),
-                translation: Vec3::new(0.0, 0.0, 5.0),
-                ..Default::default()
-            },
-            ..Default::default()
-        })
-        .insert(_CrackerComponent);
+    for child in children {
+        if let Ok(mut text) = text_query.get_mut(*child) {
+            text.sections[0].value = cracker_label(data);
+        }
+    }
+    true
 }
 
-fn _create_cracker_text(mut commands: Commands, asset_server: Res<AssetServer>) {
-    println!("inserting text!!");
-
+fn spawn_cracker(commands: &mut Commands, asset_server: &AssetServer, data: &CrackerData) {
     commands
-        .spawn(Text2dBundle {
-            text: Text::from_section(
-                "10".to_string(), // The text to display
-                TextStyle {
-                    font: asset_server.load("FiraSans-Bold.ttf"),
-                    font_size: 16.0,
-                    color: Color::WHITE,
+        .spawn((
+            Name::new(format!("Cracker {}", data.cracker_id)),
+            Cracker {
+                id: data.cracker_id.clone(),
+                kind: data.kind,
+            },
+            SpriteBundle {
+                texture: asset_server.load("images/cracker-v1.png"),
+                sprite: Sprite {
+                    color: data.kind.tint(),
+                    custom_size: Some(data.kind.size()),
+                    ..default()
                 },
-            ),
-            transform: Transform {
-                translation: Vec3::new(0.0, CRACKER_TEXT_OFFSET, 5.0), // Position the text above the crackers & in front of the background
-                scale: Vec3::new(1., 1., 1.),
-                ..Default::default()
+                transform: Transform::from_xyz(data.x_position, data.y_position, CRACKER_Z),
+                ..default()
             },
-            ..Default::default()
+            MinimapIcon::CRACKER,
+            StateScoped(Screen::Gameplay),
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                CrackerText,
+                Text2dBundle {
+                    text: Text::from_section(
+                        cracker_label(data),
+                        TextStyle {
+                            font: asset_server.load("FiraSans-Bold.ttf"),
+                            font_size: 16.0,
+                            color: Color::WHITE,
+                        },
+                    ),
+                    // Position the text above the cracker
+                    transform: Transform::from_xyz(0.0, CRACKER_TEXT_OFFSET, 0.1),
+                    ..default()
+                },
+            ));
+        });
+}
+
+/// Ask the server for the nearest cracker within reach when the player
+/// interacts. The server still decides whether it's close enough.
+fn pick_up_nearest_cracker(
+    player_query: Query<(&GlobalTransform, &PickupRadius), With<Player>>,
+    cracker_query: Query<(&Cracker, &GlobalTransform)>,
+    mut interact_request_event_writer: EventWriter<InteractRequestEvent>,
+) {
+    let Ok((player_transform, pickup_radius)) = player_query.get_single() else {
+        return;
+    };
+    let player_position = player_transform.translation().truncate();
+    let reach = pickup_radius.0 * INTERACT_REACH_FACTOR;
+
+    let nearest = cracker_query
+        .iter()
+        .map(|(cracker, transform)| {
+            let distance = player_position.distance(transform.translation().truncate());
+            (cracker, distance)
         })
-        .insert(_CrackerText);
+        .filter(|(_, distance)| *distance <= reach)
+        .min_by(|(_, a), (_, b)| a.total_cmp(b));
+    if let Some((cracker, _)) = nearest {
+        interact_request_event_writer.send(InteractRequestEvent {
+            cracker_id: cracker.id.clone(),
+        });
+    }
+}
+
+/// Ask the server to pick up any cracker within our [`PickupRadius`].
+fn request_pickups_in_range(
+    time: Res<Time>,
+    player_query: Query<(&GlobalTransform, &PickupRadius), With<Player>>,
+    cracker_query: Query<(&Cracker, &GlobalTransform)>,
+    mut requested: Local<Vec<(String, f32)>>,
+    mut interact_request_event_writer: EventWriter<InteractRequestEvent>,
+) {
+    let now = time.elapsed_seconds();
+    requested.retain(|(_, at)| now - at < PICKUP_RETRY_SECONDS);
+
+    let Ok((player_transform, pickup_radius)) = player_query.get_single() else {
+        return;
+    };
+    let player_position = player_transform.translation().truncate();
+
+    for (cracker, cracker_transform) in &cracker_query {
+        let distance = player_position.distance(cracker_transform.translation().truncate());
+        if distance > pickup_radius.0 || requested.iter().any(|(id, _)| *id == cracker.id) {
+            continue;
+        }
+        interact_request_event_writer.send(InteractRequestEvent {
+            cracker_id: cracker.id.clone(),
+        });
+        requested.push((cracker.id.clone(), now));
+    }
 }
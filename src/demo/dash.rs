@@ -0,0 +1,302 @@
+//! Dashing: a short burst of speed that costs stamina.
+//!
+//! Press [`InputAction::Dash`] or the dash button while moving to dash. Each
+//! dash drains some [`Stamina`], which refills over time, shown by a bar on the
+//! HUD. While dashing, the [`MovementController`] moves faster and tells the
+//! server it's dashing, so the server can still check every move. The duck
+//! leaves a trail of fading ghosts behind it.
+
+use bevy::prelude::*;
+use rand::prelude::*;
+
+use crate::{
+    audio::{AudioChannel, PlaySfx},
+    input::input_map::{ActionInput, InputAction},
+    screens::Screen,
+    AppSet,
+};
+
+use super::{
+    death::player_alive,
+    movement::MovementController,
+    player::{Player, PlayerAssets},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<Stamina>();
+    app.register_type::<Dashing>();
+
+    app.add_systems(
+        OnEnter(Screen::Gameplay),
+        (spawn_dash_button, spawn_stamina_bar),
+    );
+    app.add_systems(
+        Update,
+        (
+            (tick_dash, regenerate_stamina, fade_dash_ghosts).in_set(AppSet::TickTimers),
+            start_dash
+                .run_if(player_alive.and_then(resource_exists::<PlayerAssets>))
+                .in_set(AppSet::RecordInput),
+            (spawn_dash_ghosts, update_stamina_bar).in_set(AppSet::Update),
+        )
+            .run_if(in_state(Screen::Gameplay)),
+    );
+}
+
+/// How long a dash lasts, in seconds.
+const DASH_SECONDS: f32 = 0.25;
+const DASH_STAMINA_COST: f32 = 35.0;
+const MAX_STAMINA: f32 = 100.0;
+/// Stamina regained per second while not dashing.
+const STAMINA_REGEN: f32 = 20.0;
+/// How often a dashing duck leaves a ghost behind, in seconds.
+const GHOST_INTERVAL: f32 = 0.04;
+/// How long a ghost takes to fade, in seconds.
+const GHOST_SECONDS: f32 = 0.3;
+const GHOST_ALPHA: f32 = 0.5;
+/// There's no whoosh sound yet, so this is a step sped up into a scuff.
+const WHOOSH_SPEED: f32 = 1.8;
+const WHOOSH_VOLUME: f32 = 0.7;
+
+const STAMINA_BAR_COLOR: Color = Color::srgb(0.35, 0.8, 0.45);
+/// Used while there isn't enough stamina for another dash.
+const STAMINA_BAR_LOW_COLOR: Color = Color::srgb(0.6, 0.6, 0.6);
+
+/// How much dashing the duck has left in it.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Reflect)]
+#[reflect(Component)]
+pub struct Stamina {
+    pub current: f32,
+    pub max: f32,
+}
+
+impl Default for Stamina {
+    fn default() -> Self {
+        Self {
+            current: MAX_STAMINA,
+            max: MAX_STAMINA,
+        }
+    }
+}
+
+/// On a duck while it's dashing.
+#[derive(Component, Debug, Clone, PartialEq, Reflect)]
+#[reflect(Component)]
+pub struct Dashing {
+    timer: Timer,
+    ghost_timer: Timer,
+}
+
+/// A fading copy of a dashing duck.
+#[derive(Component)]
+struct DashGhost(Timer);
+
+#[derive(Component)]
+struct DashButton;
+
+#[derive(Component)]
+struct StaminaBarFill;
+
+fn start_dash(
+    mut commands: Commands,
+    input: ActionInput,
+    player_assets: Res<PlayerAssets>,
+    mut play_sfx: EventWriter<PlaySfx>,
+    button_query: Query<&Interaction, (Changed<Interaction>, With<DashButton>)>,
+    mut player_query: Query<
+        (Entity, &mut MovementController, &mut Stamina),
+        (With<Player>, Without<Dashing>),
+    >,
+) {
+    let pressed = input.just_pressed(InputAction::Dash)
+        || button_query
+            .iter()
+            .any(|interaction| *interaction == Interaction::Pressed);
+    if !pressed {
+        return;
+    }
+
+    for (entity, mut controller, mut stamina) in &mut player_query {
+        // Dashing on the spot would only waste stamina.
+        if controller.intent == Vec2::ZERO || stamina.current < DASH_STAMINA_COST {
+            continue;
+        }
+        stamina.current -= DASH_STAMINA_COST;
+        controller.dashing = true;
+        commands.entity(entity).insert(Dashing {
+            timer: Timer::from_seconds(DASH_SECONDS, TimerMode::Once),
+            ghost_timer: Timer::from_seconds(GHOST_INTERVAL, TimerMode::Repeating),
+        });
+
+        let rng = &mut rand::thread_rng();
+        let whoosh = player_assets.steps.choose(rng).unwrap();
+        play_sfx.send(
+            PlaySfx::new(AudioChannel::Sfx, whoosh.clone())
+                .with_speed(WHOOSH_SPEED)
+                .with_volume(WHOOSH_VOLUME),
+        );
+    }
+}
+
+fn tick_dash(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut dashing_query: Query<(Entity, &mut Dashing, &mut MovementController)>,
+) {
+    for (entity, mut dashing, mut controller) in &mut dashing_query {
+        dashing.timer.tick(time.delta());
+        dashing.ghost_timer.tick(time.delta());
+        if dashing.timer.finished() {
+            controller.dashing = false;
+            commands.entity(entity).remove::<Dashing>();
+        }
+    }
+}
+
+fn regenerate_stamina(time: Res<Time>, mut stamina_query: Query<&mut Stamina, Without<Dashing>>) {
+    for mut stamina in &mut stamina_query {
+        if stamina.current < stamina.max {
+            stamina.current =
+                (stamina.current + STAMINA_REGEN * time.delta_seconds()).min(stamina.max);
+        }
+    }
+}
+
+fn spawn_dash_ghosts(
+    mut commands: Commands,
+    dashing_query: Query<(&Dashing, &Handle<Image>, &Sprite, &TextureAtlas, &Transform)>,
+) {
+    for (dashing, texture, sprite, atlas, transform) in &dashing_query {
+        if !dashing.ghost_timer.just_finished() {
+            continue;
+        }
+        let mut transform = *transform;
+        // Just behind the duck.
+        transform.translation.z -= 0.1;
+        commands.spawn((
+            Name::new("Dash ghost"),
+            DashGhost(Timer::from_seconds(GHOST_SECONDS, TimerMode::Once)),
+            SpriteBundle {
+                texture: texture.clone(),
+                sprite: Sprite {
+                    color: sprite.color.with_alpha(GHOST_ALPHA),
+                    flip_x: sprite.flip_x,
+                    ..default()
+                },
+                transform,
+                ..default()
+            },
+            atlas.clone(),
+            StateScoped(Screen::Gameplay),
+        ));
+    }
+}
+
+fn fade_dash_ghosts(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut ghost_query: Query<(Entity, &mut DashGhost, &mut Sprite)>,
+) {
+    for (entity, mut ghost, mut sprite) in &mut ghost_query {
+        ghost.0.tick(time.delta());
+        if ghost.0.finished() {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+        sprite
+            .color
+            .set_alpha(GHOST_ALPHA * ghost.0.fraction_remaining());
+    }
+}
+
+fn spawn_dash_button(mut commands: Commands, asset_server: Res<AssetServer>) {
+    // Stacked on top of the quack button.
+    commands
+        .spawn((
+            Name::new("Dash button"),
+            DashButton,
+            ButtonBundle {
+                style: Style {
+                    width: Val::Px(200.0),
+                    height: Val::Px(65.0),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    position_type: PositionType::Absolute,
+                    right: Val::Percent(5.0),
+                    bottom: Val::Percent(5.0),
+                    margin: UiRect::bottom(Val::Px(75.0)),
+                    ..default()
+                },
+                background_color: Color::srgb(0.15, 0.15, 0.15).into(),
+                ..default()
+            },
+            StateScoped(Screen::Gameplay),
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                "Dash",
+                TextStyle {
+                    font: asset_server.load("FiraSans-Bold.ttf"),
+                    font_size: 28.0,
+                    color: Color::WHITE,
+                },
+            ));
+        });
+}
+
+fn spawn_stamina_bar(mut commands: Commands) {
+    commands
+        .spawn((
+            Name::new("Stamina bar"),
+            NodeBundle {
+                style: Style {
+                    width: Val::Px(200.0),
+                    height: Val::Px(12.0),
+                    position_type: PositionType::Absolute,
+                    right: Val::Percent(5.0),
+                    bottom: Val::Percent(5.0),
+                    margin: UiRect::bottom(Val::Px(150.0)),
+                    ..default()
+                },
+                background_color: Color::srgba(0.0, 0.0, 0.0, 0.5).into(),
+                ..default()
+            },
+            StateScoped(Screen::Gameplay),
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                StaminaBarFill,
+                NodeBundle {
+                    style: Style {
+                        width: Val::Percent(100.0),
+                        height: Val::Percent(100.0),
+                        ..default()
+                    },
+                    background_color: STAMINA_BAR_COLOR.into(),
+                    ..default()
+                },
+            ));
+        });
+}
+
+fn update_stamina_bar(
+    stamina_query: Query<&Stamina, With<Player>>,
+    mut fill_query: Query<(&mut Style, &mut BackgroundColor), With<StaminaBarFill>>,
+) {
+    let Ok(stamina) = stamina_query.get_single() else {
+        return;
+    };
+    let width = Val::Percent(100.0 * stamina.current / stamina.max);
+    let color = if stamina.current < DASH_STAMINA_COST {
+        STAMINA_BAR_LOW_COLOR
+    } else {
+        STAMINA_BAR_COLOR
+    };
+
+    for (mut style, mut background) in &mut fill_query {
+        if style.width != width {
+            style.width = width;
+        }
+        background.set_if_neq(color.into());
+    }
+}
@@ -0,0 +1,302 @@
+//! A day/night cycle everyone sees the same.
+//!
+//! The server sends the time of day in the `YouJoined` snapshot, and the
+//! [`WorldClock`] runs on locally from there. The time of day drives a tint
+//! over the world, a crossfade between the day and night music, and a small
+//! clock on the HUD.
+
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::{
+    asset_tracking::LoadResource,
+    music::{MusicManager, Playlist, Track},
+    screens::Screen,
+    AppSet,
+};
+
+use super::websocket_connect::YouJoinedWsReceived;
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<WorldClock>();
+    app.init_resource::<WorldClock>();
+    app.load_resource::<DayNightMusic>();
+
+    app.add_systems(
+        OnEnter(Screen::Gameplay),
+        (spawn_night_tint, spawn_clock_widget),
+    );
+    app.add_systems(
+        Update,
+        (
+            tick_world_clock.in_set(AppSet::TickTimers),
+            (sync_world_clock, (update_night_tint, update_clock_widget))
+                .chain()
+                .in_set(AppSet::Update),
+        )
+            .run_if(in_state(Screen::Gameplay)),
+    );
+    // Even before gameplay starts, so the right music is ready for it.
+    app.add_systems(
+        Update,
+        set_day_night_playlist
+            .run_if(resource_exists::<DayNightMusic>)
+            .after(sync_world_clock)
+            .in_set(AppSet::Update),
+    );
+}
+
+/// Used until the server says otherwise.
+const DEFAULT_DAY_LENGTH_SECONDS: f32 = 600.0;
+const DEFAULT_TIME_OF_DAY: f32 = 0.35;
+/// The night track is the credits music, slowed down to sound sleepier.
+const NIGHT_MUSIC_SPEED: f32 = 0.8;
+/// Big enough to cover the view at any zoom level.
+const TINT_SIZE: f32 = 100_000.0;
+/// Above every world sprite, but still in view of the 2D camera. UI isn't
+/// tinted.
+const TINT_Z: f32 = 998.0;
+
+/// The tint over the world through the day, as `(time of day, color)`. The
+/// alpha is how strongly it tints.
+const TINT_KEYFRAMES: [(f32, Color); 8] = [
+    (0.0, Color::srgba(0.05, 0.07, 0.25, 0.55)),
+    (0.22, Color::srgba(0.1, 0.1, 0.3, 0.45)),
+    (0.27, Color::srgba(1.0, 0.55, 0.3, 0.2)),
+    (0.33, Color::srgba(1.0, 0.9, 0.7, 0.0)),
+    (0.7, Color::srgba(1.0, 0.9, 0.7, 0.0)),
+    (0.77, Color::srgba(1.0, 0.45, 0.25, 0.25)),
+    (0.83, Color::srgba(0.1, 0.1, 0.3, 0.45)),
+    (1.0, Color::srgba(0.05, 0.07, 0.25, 0.55)),
+];
+
+/// The shared time of day.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Reflect)]
+#[reflect(Resource)]
+pub struct WorldClock {
+    /// From 0 to 1, where 0 is midnight and 0.5 is noon.
+    pub time_of_day: f32,
+    /// How long a full day takes, in real seconds.
+    pub day_length_seconds: f32,
+}
+
+impl Default for WorldClock {
+    fn default() -> Self {
+        Self {
+            time_of_day: DEFAULT_TIME_OF_DAY,
+            day_length_seconds: DEFAULT_DAY_LENGTH_SECONDS,
+        }
+    }
+}
+
+impl WorldClock {
+    /// How much it's night, from 0 in daylight to 1 in the dead of night.
+    pub fn night_amount(&self) -> f32 {
+        let t = self.time_of_day;
+        let dawn = 1.0 - ((t - 0.2) / 0.1).clamp(0.0, 1.0);
+        let dusk = ((t - 0.75) / 0.1).clamp(0.0, 1.0);
+        dawn.max(dusk)
+    }
+
+    fn tint(&self) -> Color {
+        let t = self.time_of_day;
+        let next = TINT_KEYFRAMES
+            .iter()
+            .position(|(at, _)| *at >= t)
+            .unwrap_or(TINT_KEYFRAMES.len() - 1)
+            .max(1);
+        let (from_t, from) = TINT_KEYFRAMES[next - 1];
+        let (to_t, to) = TINT_KEYFRAMES[next];
+        let blend = ((t - from_t) / (to_t - from_t)).clamp(0.0, 1.0);
+        from.mix(&to, blend)
+    }
+
+    /// The time of day as a 24-hour `HH:MM`.
+    fn format(&self) -> String {
+        let minutes = (self.time_of_day * 24.0 * 60.0) as u32 % (24 * 60);
+        format!("{:02}:{:02}", minutes / 60, minutes % 60)
+    }
+}
+
+/// The clock in the `YouJoined` snapshot.
+#[derive(Debug, Deserialize)]
+struct WorldClockData {
+    time_of_day: f32,
+    day_length_seconds: f32,
+}
+
+#[derive(Debug, Deserialize)]
+struct YouJoinedClockData {
+    world_clock: Option<WorldClockData>,
+}
+
+#[derive(Component)]
+struct NightTint;
+
+#[derive(Component)]
+struct ClockText;
+
+#[derive(Resource, Asset, Reflect, Clone)]
+pub struct DayNightMusic {
+    #[dependency]
+    day: Handle<AudioSource>,
+    #[dependency]
+    night: Handle<AudioSource>,
+}
+
+impl FromWorld for DayNightMusic {
+    fn from_world(world: &mut World) -> Self {
+        let assets = world.resource::<AssetServer>();
+        Self {
+            day: assets.load("audio/music/Fluffing A Duck.ogg"),
+            night: assets.load("audio/music/Monkeys Spinning Monkeys.ogg"),
+        }
+    }
+}
+
+impl DayNightMusic {
+    fn playlist(&self, night: bool) -> Playlist {
+        if night {
+            Playlist::new(
+                "Night",
+                [Track::new(self.night.clone()).with_speed(NIGHT_MUSIC_SPEED)],
+            )
+        } else {
+            Playlist::new("Day", [Track::new(self.day.clone())])
+        }
+    }
+}
+
+fn sync_world_clock(
+    mut event_reader: EventReader<YouJoinedWsReceived>,
+    mut clock: ResMut<WorldClock>,
+) {
+    for e in event_reader.read() {
+        let world_clock = match serde_json::from_value::<YouJoinedClockData>(e.data.clone()) {
+            Ok(data) => data.world_clock,
+            Err(op) => {
+                info!("Failed to parse incoming websocket message: {}", op);
+                None
+            }
+        };
+        let Some(world_clock) = world_clock else {
+            warn!("The server didn't send a world clock, using a local one");
+            continue;
+        };
+
+        *clock = WorldClock {
+            time_of_day: world_clock.time_of_day.rem_euclid(1.0),
+            day_length_seconds: world_clock.day_length_seconds.max(1.0),
+        };
+    }
+}
+
+fn tick_world_clock(time: Res<Time>, mut clock: ResMut<WorldClock>) {
+    let day_length_seconds = clock.day_length_seconds;
+    clock.time_of_day = (clock.time_of_day + time.delta_seconds() / day_length_seconds).fract();
+}
+
+fn spawn_night_tint(mut commands: Commands, clock: Res<WorldClock>) {
+    commands.spawn((
+        Name::new("Night tint"),
+        NightTint,
+        SpriteBundle {
+            sprite: Sprite {
+                color: clock.tint(),
+                custom_size: Some(Vec2::splat(TINT_SIZE)),
+                ..default()
+            },
+            transform: Transform::from_xyz(0.0, 0.0, TINT_Z),
+            ..default()
+        },
+        StateScoped(Screen::Gameplay),
+    ));
+}
+
+fn update_night_tint(
+    clock: Res<WorldClock>,
+    camera_query: Query<&Transform, (With<Camera>, Without<NightTint>)>,
+    mut tint_query: Query<(&mut Transform, &mut Sprite), With<NightTint>>,
+) {
+    let Ok(camera_transform) = camera_query.get_single() else {
+        return;
+    };
+    for (mut transform, mut sprite) in &mut tint_query {
+        // Follow the camera, so it never runs out at the edges.
+        transform.translation.x = camera_transform.translation.x;
+        transform.translation.y = camera_transform.translation.y;
+        sprite.color = clock.tint();
+    }
+}
+
+/// Crossfades to the night music as it gets dark, and back at dawn.
+fn set_day_night_playlist(
+    clock: Res<WorldClock>,
+    music: Res<DayNightMusic>,
+    mut manager: ResMut<MusicManager>,
+) {
+    let playlist = music.playlist(clock.night_amount() > 0.5);
+    let current = manager
+        .playlist(&Screen::Gameplay)
+        .map(|playlist| playlist.name);
+    if current != Some(playlist.name) {
+        manager.set_playlist(Screen::Gameplay, playlist);
+    }
+}
+
+fn spawn_clock_widget(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    clock: Res<WorldClock>,
+) {
+    commands
+        .spawn((
+            Name::new("Clock"),
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    top: Val::Percent(3.),
+                    left: Val::Percent(50.),
+                    width: Val::Px(120.0),
+                    margin: UiRect::left(Val::Px(-60.0)),
+                    padding: UiRect::axes(Val::Px(8.0), Val::Px(4.0)),
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                background_color: Color::srgba(0.0, 0.0, 0.0, 0.35).into(),
+                ..default()
+            },
+            StateScoped(Screen::Gameplay),
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                ClockText,
+                TextBundle::from_section(
+                    clock_label(&clock),
+                    TextStyle {
+                        font: asset_server.load("FiraSans-Bold.ttf"),
+                        font_size: 20.0,
+                        color: Color::WHITE,
+                    },
+                ),
+            ));
+        });
+}
+
+fn update_clock_widget(clock: Res<WorldClock>, mut text_query: Query<&mut Text, With<ClockText>>) {
+    let label = clock_label(&clock);
+    for mut text in &mut text_query {
+        if text.sections[0].value != label {
+            text.sections[0].value = label.clone();
+        }
+    }
+}
+
+fn clock_label(clock: &WorldClock) -> String {
+    let period = if clock.night_amount() > 0.5 {
+        "Night"
+    } else {
+        "Day"
+    };
+    format!("{period} {}", clock.format())
+}
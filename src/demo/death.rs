@@ -0,0 +1,463 @@
+//! Getting eaten, and coming back.
+//!
+//! When the server says a duck died, it spins, shrinks and fades out in a
+//! burst of feathers. Your own duck then stays [`Dead`] (ignoring input) while
+//! a "You got eaten!" overlay counts down to the respawn and shows how the
+//! session went. Other ducks either respawn where the server says, or are
+//! despawned if it doesn't give a position.
+
+use bevy::{prelude::*, ui::FocusPolicy};
+use rand::prelude::*;
+use serde::Deserialize;
+
+use crate::{
+    audio::{AudioChannel, PlaySfx, SfxPriority},
+    screens::Screen,
+    theme::prelude::*,
+    AppSet,
+};
+
+use super::{
+    interpolation::NetworkPosition,
+    movement::MovementController,
+    other_player::OtherPlayer,
+    player::Player,
+    websocket_connect::{
+        MoveCrackersBevyEvent, OtherPlayerDiedWsReceived, UpdateYourScoreBevyEvent,
+        YouDiedWsReceived,
+    },
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_event::<DuckDied>();
+    app.init_resource::<SessionStats>();
+
+    app.add_systems(OnEnter(Screen::Gameplay), reset_session_stats);
+    app.add_systems(
+        Update,
+        (
+            (tick_dying, tick_respawn, tick_feathers).in_set(AppSet::TickTimers),
+            (
+                (you_died_handler, other_player_died_handler),
+                start_dying,
+                (
+                    animate_dying,
+                    animate_feathers,
+                    track_session_stats,
+                    update_death_overlay,
+                ),
+            )
+                .chain()
+                .in_set(AppSet::Update),
+        )
+            .run_if(in_state(Screen::Gameplay)),
+    );
+}
+
+const DYING_SECONDS: f32 = 1.0;
+/// Used if the server doesn't say how long to wait.
+const DEFAULT_RESPAWN_SECONDS: f32 = 3.0;
+const FEATHER_COUNT: usize = 10;
+const FEATHER_SECONDS: f32 = 0.7;
+const FEATHER_SPEED: f32 = 260.0;
+const FEATHER_SIZE: Vec2 = Vec2::new(10.0, 5.0);
+/// How much slower than a quack the death sound plays.
+const DEATH_SOUND_SPEED: f32 = 0.5;
+
+/// Your duck is dead, and waiting to respawn. Input is ignored meanwhile.
+#[derive(Component)]
+pub struct Dead {
+    respawn_at: Vec2,
+    timer: Timer,
+}
+
+/// Run condition that is false while your duck is [`Dead`].
+pub fn player_alive(dead_query: Query<(), (With<Player>, With<Dead>)>) -> bool {
+    dead_query.is_empty()
+}
+
+/// How your session has gone so far, shown when you get eaten.
+#[derive(Resource, Debug, Default)]
+pub struct SessionStats {
+    pub score: u64,
+    pub best_score: u64,
+    pub crackers_eaten: u32,
+    pub deaths: u32,
+    /// When your duck last (re)spawned, in [`Time::elapsed_seconds`].
+    pub alive_since: f32,
+}
+
+/// A duck died. Used for both your duck and other players' ducks.
+#[derive(Event, Debug, Clone, Copy)]
+struct DuckDied {
+    duck: Entity,
+    /// Where an other player's duck comes back, if it does. Your own duck
+    /// respawns through [`Dead`] instead.
+    respawn_at: Option<Vec2>,
+}
+
+/// A duck playing its death animation.
+#[derive(Component)]
+struct Dying {
+    timer: Timer,
+    /// Where an other player's duck comes back, if it does.
+    respawn_at: Option<Vec2>,
+    /// The duck's scale before dying, restored when it comes back.
+    base_scale: Vec3,
+}
+
+#[derive(Component)]
+struct Feather {
+    velocity: Vec2,
+    timer: Timer,
+}
+
+#[derive(Component)]
+struct DeathOverlay;
+
+#[derive(Component)]
+struct RespawnCountdownText;
+
+#[derive(Debug, Deserialize)]
+struct YouDiedData {
+    respawn_x_position: f32,
+    respawn_y_position: f32,
+    respawn_seconds: Option<f32>,
+    /// Whatever ate you, e.g. "a fox".
+    eaten_by: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OtherPlayerDiedData {
+    player_uuid: String,
+    respawn_x_position: Option<f32>,
+    respawn_y_position: Option<f32>,
+}
+
+fn reset_session_stats(mut stats: ResMut<SessionStats>, time: Res<Time>) {
+    *stats = SessionStats {
+        alive_since: time.elapsed_seconds(),
+        ..default()
+    };
+}
+
+fn track_session_stats(
+    mut bevy_move_crackers_event_reader: EventReader<MoveCrackersBevyEvent>,
+    mut update_your_score_event_reader: EventReader<UpdateYourScoreBevyEvent>,
+    mut stats: ResMut<SessionStats>,
+) {
+    for e in bevy_move_crackers_event_reader.read() {
+        if e.you_got_crackers {
+            stats.crackers_eaten += 1;
+        }
+    }
+    for e in update_your_score_event_reader.read() {
+        stats.score = e.new_score;
+        stats.best_score = stats.best_score.max(e.new_score);
+    }
+}
+
+fn you_died_handler(
+    mut commands: Commands,
+    mut event_reader: EventReader<YouDiedWsReceived>,
+    time: Res<Time>,
+    mut stats: ResMut<SessionStats>,
+    mut player_query: Query<(Entity, &mut MovementController), With<Player>>,
+    mut duck_died_event_writer: EventWriter<DuckDied>,
+) {
+    for e in event_reader.read() {
+        let data: YouDiedData = match serde_json::from_value(e.data.clone()) {
+            Ok(data) => data,
+            Err(op) => {
+                info!("Failed to parse incoming websocket message: {}", op);
+                continue;
+            }
+        };
+        let Ok((duck, mut controller)) = player_query.get_single_mut() else {
+            continue;
+        };
+
+        stats.deaths += 1;
+        controller.intent = Vec2::ZERO;
+        controller.velocity = Vec2::ZERO;
+        let respawn_seconds = data.respawn_seconds.unwrap_or(DEFAULT_RESPAWN_SECONDS);
+        commands.entity(duck).insert(Dead {
+            respawn_at: Vec2::new(data.respawn_x_position, data.respawn_y_position),
+            timer: Timer::from_seconds(respawn_seconds, TimerMode::Once),
+        });
+        duck_died_event_writer.send(DuckDied {
+            duck,
+            respawn_at: None,
+        });
+
+        spawn_death_overlay(&mut commands, &stats, &time, data.eaten_by.as_deref());
+    }
+}
+
+fn other_player_died_handler(
+    mut event_reader: EventReader<OtherPlayerDiedWsReceived>,
+    other_players: Query<(Entity, &Name), With<OtherPlayer>>,
+    mut duck_died_event_writer: EventWriter<DuckDied>,
+) {
+    for e in event_reader.read() {
+        let data: OtherPlayerDiedData = match serde_json::from_value(e.data.clone()) {
+            Ok(data) => data,
+            Err(op) => {
+                info!("Failed to parse incoming websocket message: {}", op);
+                continue;
+            }
+        };
+        let respawn_at = data
+            .respawn_x_position
+            .zip(data.respawn_y_position)
+            .map(|(x, y)| Vec2::new(x, y));
+
+        for (duck, name) in &other_players {
+            if name.as_str() != data.player_uuid {
+                continue;
+            }
+            duck_died_event_writer.send(DuckDied { duck, respawn_at });
+        }
+    }
+}
+
+fn start_dying(
+    mut commands: Commands,
+    mut events: EventReader<DuckDied>,
+    asset_server: Res<AssetServer>,
+    mut play_sfx: EventWriter<PlaySfx>,
+    duck_query: Query<(&Transform, &GlobalTransform, &Sprite, Option<&Dying>)>,
+    player_query: Query<(), With<Player>>,
+) {
+    let rng = &mut rand::thread_rng();
+
+    for event in events.read() {
+        let Ok((transform, global_transform, sprite, dying)) = duck_query.get(event.duck) else {
+            continue;
+        };
+        let position = global_transform.translation();
+
+        commands.entity(event.duck).insert(Dying {
+            timer: Timer::from_seconds(DYING_SECONDS, TimerMode::Once),
+            respawn_at: event.respawn_at,
+            // Dying again mid-animation mustn't keep the shrunken scale.
+            base_scale: dying.map_or(transform.scale, |dying| dying.base_scale),
+        });
+
+        for _ in 0..FEATHER_COUNT {
+            let angle = rng.gen_range(0.0..std::f32::consts::TAU);
+            let speed = FEATHER_SPEED * rng.gen_range(0.5..1.0);
+            commands.spawn((
+                Name::new("Feather"),
+                Feather {
+                    velocity: Vec2::from_angle(angle) * speed,
+                    timer: Timer::from_seconds(FEATHER_SECONDS, TimerMode::Once),
+                },
+                SpriteBundle {
+                    sprite: Sprite {
+                        color: sprite.color.with_alpha(1.0),
+                        custom_size: Some(FEATHER_SIZE),
+                        ..default()
+                    },
+                    transform: Transform::from_translation(position + Vec3::Z)
+                        .with_rotation(Quat::from_rotation_z(angle)),
+                    ..default()
+                },
+                StateScoped(Screen::Gameplay),
+            ));
+        }
+
+        // A slowed down quack makes a sad enough sound.
+        let source = asset_server.load("audio/sound_effects/duck-quack.ogg");
+        let sound = if player_query.contains(event.duck) {
+            PlaySfx::new(AudioChannel::Sfx, source)
+        } else {
+            PlaySfx::new(AudioChannel::RemotePlayers, source).at(position.truncate())
+        };
+        play_sfx.send(
+            sound
+                .with_speed(DEATH_SOUND_SPEED)
+                .with_priority(SfxPriority::High),
+        );
+    }
+}
+
+/// Spin, shrink and fade out.
+fn animate_dying(mut duck_query: Query<(&Dying, &mut Transform, &mut Sprite)>) {
+    for (dying, mut transform, mut sprite) in &mut duck_query {
+        let progress = dying.timer.fraction();
+        let shrink = 1.0 - 0.7 * progress;
+
+        transform.rotation = Quat::from_rotation_z(progress * std::f32::consts::TAU * 2.0);
+        transform.scale = (dying.base_scale.truncate() * shrink).extend(dying.base_scale.z);
+        sprite.color.set_alpha(1.0 - progress);
+    }
+}
+
+fn restore_duck(dying: &Dying, transform: &mut Transform, sprite: &mut Sprite) {
+    transform.rotation = Quat::IDENTITY;
+    transform.scale = dying.base_scale;
+    sprite.color.set_alpha(1.0);
+}
+
+fn tick_dying(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut duck_query: Query<(
+        Entity,
+        &mut Dying,
+        &mut Transform,
+        &mut Sprite,
+        &mut Visibility,
+        Option<&mut NetworkPosition>,
+        Has<Player>,
+    )>,
+) {
+    for (
+        entity,
+        mut dying,
+        mut transform,
+        mut sprite,
+        mut visibility,
+        network_position,
+        is_player,
+    ) in &mut duck_query
+    {
+        dying.timer.tick(time.delta());
+        if !dying.timer.finished() {
+            continue;
+        }
+
+        if is_player {
+            // Stay out of sight until `tick_respawn` brings you back.
+            restore_duck(&dying, &mut transform, &mut sprite);
+            *visibility = Visibility::Hidden;
+            commands.entity(entity).remove::<Dying>();
+        } else if let Some(respawn_at) = dying.respawn_at {
+            restore_duck(&dying, &mut transform, &mut sprite);
+            transform.translation.x = respawn_at.x;
+            transform.translation.y = respawn_at.y;
+            if let Some(mut network_position) = network_position {
+                network_position.0 = respawn_at;
+            }
+            commands.entity(entity).remove::<Dying>();
+        } else {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+fn tick_respawn(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut stats: ResMut<SessionStats>,
+    mut player_query: Query<
+        (
+            Entity,
+            &mut Dead,
+            Option<&Dying>,
+            &mut Transform,
+            &mut Sprite,
+            &mut Visibility,
+        ),
+        With<Player>,
+    >,
+    overlay_query: Query<Entity, With<DeathOverlay>>,
+) {
+    for (entity, mut dead, dying, mut transform, mut sprite, mut visibility) in &mut player_query {
+        dead.timer.tick(time.delta());
+        if !dead.timer.finished() {
+            continue;
+        }
+
+        // The respawn can come before the death animation is over.
+        if let Some(dying) = dying {
+            restore_duck(dying, &mut transform, &mut sprite);
+        }
+        transform.translation.x = dead.respawn_at.x;
+        transform.translation.y = dead.respawn_at.y;
+        *visibility = Visibility::Inherited;
+        commands.entity(entity).remove::<(Dead, Dying)>();
+
+        stats.alive_since = time.elapsed_seconds();
+        for overlay in &overlay_query {
+            commands.entity(overlay).despawn_recursive();
+        }
+    }
+}
+
+fn tick_feathers(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut feather_query: Query<(Entity, &mut Feather)>,
+) {
+    for (entity, mut feather) in &mut feather_query {
+        feather.timer.tick(time.delta());
+        if feather.timer.finished() {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+fn animate_feathers(
+    time: Res<Time>,
+    mut feather_query: Query<(&Feather, &mut Transform, &mut Sprite)>,
+) {
+    for (feather, mut transform, mut sprite) in &mut feather_query {
+        let progress = feather.timer.fraction();
+        // Slow down as they drift away.
+        let velocity = feather.velocity * (1.0 - progress);
+        transform.translation += (velocity * time.delta_seconds()).extend(0.0);
+        transform.rotate_z(4.0 * time.delta_seconds());
+        sprite.color.set_alpha(1.0 - progress);
+    }
+}
+
+fn spawn_death_overlay(
+    commands: &mut Commands,
+    stats: &SessionStats,
+    time: &Time,
+    eaten_by: Option<&str>,
+) {
+    let survived = (time.elapsed_seconds() - stats.alive_since).max(0.0) as u32;
+
+    commands
+        .ui_root()
+        .insert((
+            Name::new("Death overlay"),
+            DeathOverlay,
+            BackgroundColor(Color::srgba(0.3, 0.0, 0.0, 0.6)),
+            // Keep clicks and taps from reaching the game underneath.
+            FocusPolicy::Block,
+            Interaction::default(),
+            ZIndex::Global(20),
+            StateScoped(Screen::Gameplay),
+        ))
+        .with_children(|overlay| {
+            overlay.header("You got eaten!");
+            if let Some(eaten_by) = eaten_by {
+                overlay.label(format!("By {eaten_by}"));
+            }
+            overlay.label("").insert(RespawnCountdownText);
+            overlay.label(format!("Score: {}", stats.score));
+            overlay.label(format!("Best score: {}", stats.best_score));
+            overlay.label(format!("Crackers eaten: {}", stats.crackers_eaten));
+            overlay.label(format!("Survived: {}:{:02}", survived / 60, survived % 60));
+            overlay.label(format!("Times eaten: {}", stats.deaths));
+        });
+}
+
+fn update_death_overlay(
+    player_query: Query<&Dead, With<Player>>,
+    mut text_query: Query<&mut Text, With<RespawnCountdownText>>,
+) {
+    let Ok(dead) = player_query.get_single() else {
+        return;
+    };
+    let countdown = format!("Respawning in {:.0}...", dead.timer.remaining_secs().ceil());
+    for mut text in &mut text_query {
+        if text.sections[0].value != countdown {
+            text.sections[0].value = countdown.clone();
+        }
+    }
+}
@@ -0,0 +1,299 @@
+//! Duck colours: the preset palette offered at join time, and parsing of the
+//! `color` string the server sends back for every duck.
+
+use bevy::{color::palettes::css, prelude::*};
+use rand::prelude::*;
+
+pub(super) fn plugin(app: &mut App) {
+    app.insert_resource(DuckColorChoice::random());
+}
+
+/// The preset duck colours offered by the swatch picker on the title screen.
+/// The name is what gets sent to the server, so every client resolves it to
+/// the same colour.
+pub const DUCK_COLOR_PRESETS: [(&str, Color); 12] = [
+    ("white", Color::WHITE),
+    ("yellow", Color::srgba(1.0, 1.0, 0.8, 1.0)),
+    ("teal", Color::srgba(0.8, 1.0, 1.0, 1.0)),
+    ("purple", Color::srgba(0.70, 0.6, 1.0, 1.0)),
+    ("pink", Color::srgba(1.0, 0.75, 0.8, 1.0)),
+    ("orange", Color::srgba(1.0, 0.78, 0.49, 1.)),
+    ("baby_blue", Color::srgba(0.54, 0.81, 0.94, 1.)),
+    ("lime", Color::srgba(0.60, 1.0, 0.60, 1.)),
+    ("forest", Color::srgba(0.1, 0.5, 0.1, 1.)),
+    ("blue", Color::srgba(0.5, 0.5, 1.0, 1.)),
+    ("red", Color::srgba(0.5, 0.1, 0., 1.)),
+    ("green", Color::srgba(0., 0.9, 0., 1.)),
+];
+
+/// The colour the player picked for their duck, sent along with the join request.
+#[derive(Resource, Debug, Clone, PartialEq, Eq)]
+pub struct DuckColorChoice(pub String);
+
+impl DuckColorChoice {
+    /// Start with a random preset so that players who skip the picker still
+    /// end up with distinct ducks.
+    fn random() -> Self {
+        let rng = &mut rand::thread_rng();
+        let (name, _) = DUCK_COLOR_PRESETS.choose(rng).unwrap();
+        Self(name.to_string())
+    }
+}
+
+/// Turn a colour string from the server into a sprite tint.
+///
+/// Accepts, in order of precedence:
+/// - a preset name from [`DUCK_COLOR_PRESETS`] (e.g. `"baby_blue"`),
+/// - a hex colour with or without the leading `#` (e.g. `"#ffcc00"`, `"fc0"`),
+/// - a CSS named colour, ignoring case, spaces, dashes and underscores (e.g. `"Alice Blue"`).
+///
+/// Anything else falls back to white.
+pub fn unpack_duck_color(color: String) -> Color {
+    let color = color.trim();
+
+    if let Some((_, preset)) = DUCK_COLOR_PRESETS
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(color))
+    {
+        return *preset;
+    }
+
+    if color.starts_with('#') {
+        return Srgba::hex(color).map(Color::from).unwrap_or_else(|err| {
+            warn!("Couldn't parse duck color {:?}: {}", color, err);
+            Color::WHITE
+        });
+    }
+
+    let css_name: String = color
+        .chars()
+        .filter(|c| !matches!(c, ' ' | '-' | '_'))
+        .collect::<String>()
+        .to_ascii_lowercase();
+
+    if let Some(named) = css_named_color(&css_name) {
+        return named.into();
+    }
+
+    // Hex without the leading `#`, e.g. "ffcc00".
+    Srgba::hex(color).map(Color::from).unwrap_or_else(|_| {
+        warn!("Unknown duck color {:?}, falling back to white", color);
+        Color::WHITE
+    })
+}
+
+/// Look up one of the [CSS named colours](https://developer.mozilla.org/en-US/docs/Web/CSS/named-color).
+/// The name must already be lowercased with separators removed.
+fn css_named_color(name: &str) -> Option<Srgba> {
+    let color = match name {
+        "aliceblue" => css::ALICE_BLUE,
+        "antiquewhite" => css::ANTIQUE_WHITE,
+        "aqua" => css::AQUA,
+        "aquamarine" => css::AQUAMARINE,
+        "azure" => css::AZURE,
+        "beige" => css::BEIGE,
+        "bisque" => css::BISQUE,
+        "black" => css::BLACK,
+        "blanchedalmond" => css::BLANCHED_ALMOND,
+        "blue" => css::BLUE,
+        "blueviolet" => css::BLUE_VIOLET,
+        "brown" => css::BROWN,
+        "burlywood" => css::BURLYWOOD,
+        "cadetblue" => css::CADET_BLUE,
+        "chartreuse" => css::CHARTREUSE,
+        "chocolate" => css::CHOCOLATE,
+        "coral" => css::CORAL,
+        "cornflowerblue" => css::CORNFLOWER_BLUE,
+        "cornsilk" => css::CORNSILK,
+        "crimson" => css::CRIMSON,
+        "cyan" => css::AQUA,
+        "darkblue" => css::DARK_BLUE,
+        "darkcyan" => css::DARK_CYAN,
+        "darkgoldenrod" => css::DARK_GOLDENROD,
+        "darkgray" => css::DARK_GRAY,
+        "darkgreen" => css::DARK_GREEN,
+        "darkgrey" => css::DARK_GREY,
+        "darkkhaki" => css::DARK_KHAKI,
+        "darkmagenta" => css::DARK_MAGENTA,
+        "darkolivegreen" => css::DARK_OLIVEGREEN,
+        "darkorange" => css::DARK_ORANGE,
+        "darkorchid" => css::DARK_ORCHID,
+        "darkred" => css::DARK_RED,
+        "darksalmon" => css::DARK_SALMON,
+        "darkseagreen" => css::DARK_SEA_GREEN,
+        "darkslateblue" => css::DARK_SLATE_BLUE,
+        "darkslategray" => css::DARK_SLATE_GRAY,
+        "darkslategrey" => css::DARK_SLATE_GREY,
+        "darkturquoise" => css::DARK_TURQUOISE,
+        "darkviolet" => css::DARK_VIOLET,
+        "deeppink" => css::DEEP_PINK,
+        "deepskyblue" => css::DEEP_SKY_BLUE,
+        "dimgray" => css::DIM_GRAY,
+        "dimgrey" => css::DIM_GREY,
+        "dodgerblue" => css::DODGER_BLUE,
+        "firebrick" => css::FIRE_BRICK,
+        "floralwhite" => css::FLORAL_WHITE,
+        "forestgreen" => css::FOREST_GREEN,
+        "fuchsia" => css::FUCHSIA,
+        "gainsboro" => css::GAINSBORO,
+        "ghostwhite" => css::GHOST_WHITE,
+        "gold" => css::GOLD,
+        "goldenrod" => css::GOLDENROD,
+        "gray" => css::GRAY,
+        "green" => css::GREEN,
+        "greenyellow" => css::GREEN_YELLOW,
+        "grey" => css::GREY,
+        "honeydew" => css::HONEYDEW,
+        "hotpink" => css::HOT_PINK,
+        "indianred" => css::INDIAN_RED,
+        "indigo" => css::INDIGO,
+        "ivory" => css::IVORY,
+        "khaki" => css::KHAKI,
+        "lavender" => css::LAVENDER,
+        "lavenderblush" => css::LAVENDER_BLUSH,
+        "lawngreen" => css::LAWN_GREEN,
+        "lemonchiffon" => css::LEMON_CHIFFON,
+        "lightblue" => css::LIGHT_BLUE,
+        "lightcoral" => css::LIGHT_CORAL,
+        "lightcyan" => css::LIGHT_CYAN,
+        "lightgoldenrodyellow" => css::LIGHT_GOLDENROD_YELLOW,
+        "lightgray" => css::LIGHT_GRAY,
+        "lightgreen" => css::LIGHT_GREEN,
+        "lightgrey" => css::LIGHT_GREY,
+        "lightpink" => css::LIGHT_PINK,
+        "lightsalmon" => css::LIGHT_SALMON,
+        "lightseagreen" => css::LIGHT_SEA_GREEN,
+        "lightskyblue" => css::LIGHT_SKY_BLUE,
+        "lightslategray" => css::LIGHT_SLATE_GRAY,
+        "lightslategrey" => css::LIGHT_SLATE_GREY,
+        "lightsteelblue" => css::LIGHT_STEEL_BLUE,
+        "lightyellow" => css::LIGHT_YELLOW,
+        "lime" => css::LIME,
+        "limegreen" => css::LIMEGREEN,
+        "linen" => css::LINEN,
+        "magenta" => css::MAGENTA,
+        "maroon" => css::MAROON,
+        "mediumaquamarine" => css::MEDIUM_AQUAMARINE,
+        "mediumblue" => css::MEDIUM_BLUE,
+        "mediumorchid" => css::MEDIUM_ORCHID,
+        "mediumpurple" => css::MEDIUM_PURPLE,
+        "mediumseagreen" => css::MEDIUM_SEA_GREEN,
+        "mediumslateblue" => css::MEDIUM_SLATE_BLUE,
+        "mediumspringgreen" => css::MEDIUM_SPRING_GREEN,
+        "mediumturquoise" => css::MEDIUM_TURQUOISE,
+        "mediumvioletred" => css::MEDIUM_VIOLET_RED,
+        "midnightblue" => css::MIDNIGHT_BLUE,
+        "mintcream" => css::MINT_CREAM,
+        "mistyrose" => css::MISTY_ROSE,
+        "moccasin" => css::MOCCASIN,
+        "navajowhite" => css::NAVAJO_WHITE,
+        "navy" => css::NAVY,
+        "oldlace" => css::OLD_LACE,
+        "olive" => css::OLIVE,
+        "olivedrab" => css::OLIVE_DRAB,
+        "orange" => css::ORANGE,
+        "orangered" => css::ORANGE_RED,
+        "orchid" => css::ORCHID,
+        "palegoldenrod" => css::PALE_GOLDENROD,
+        "palegreen" => css::PALE_GREEN,
+        "paleturquoise" => css::PALE_TURQUOISE,
+        "palevioletred" => css::PALE_VIOLETRED,
+        "papayawhip" => css::PAPAYA_WHIP,
+        "peachpuff" => css::PEACHPUFF,
+        "peru" => css::PERU,
+        "pink" => css::PINK,
+        "plum" => css::PLUM,
+        "powderblue" => css::POWDER_BLUE,
+        "purple" => css::PURPLE,
+        "rebeccapurple" => css::REBECCA_PURPLE,
+        "red" => css::RED,
+        "rosybrown" => css::ROSY_BROWN,
+        "royalblue" => css::ROYAL_BLUE,
+        "saddlebrown" => css::SADDLE_BROWN,
+        "salmon" => css::SALMON,
+        "sandybrown" => css::SANDY_BROWN,
+        "seagreen" => css::SEA_GREEN,
+        "seashell" => css::SEASHELL,
+        "sienna" => css::SIENNA,
+        "silver" => css::SILVER,
+        "skyblue" => css::SKY_BLUE,
+        "slateblue" => css::SLATE_BLUE,
+        "slategray" => css::SLATE_GRAY,
+        "slategrey" => css::SLATE_GREY,
+        "snow" => css::SNOW,
+        "springgreen" => css::SPRING_GREEN,
+        "steelblue" => css::STEEL_BLUE,
+        "tan" => css::TAN,
+        "teal" => css::TEAL,
+        "thistle" => css::THISTLE,
+        "tomato" => css::TOMATO,
+        "turquoise" => css::TURQUOISE,
+        "violet" => css::VIOLET,
+        "wheat" => css::WHEAT,
+        "white" => css::WHITE,
+        "whitesmoke" => css::WHITE_SMOKE,
+        "yellow" => css::YELLOW,
+        "yellowgreen" => css::YELLOW_GREEN,
+        _ => return None,
+    };
+    Some(color)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unpack(color: &str) -> Color {
+        unpack_duck_color(color.to_string())
+    }
+
+    #[test]
+    fn presets_win_over_css_names() {
+        let (_, baby_blue) = DUCK_COLOR_PRESETS[6];
+        assert_eq!(unpack("baby_blue"), baby_blue);
+        assert_eq!(unpack(" Baby_Blue "), baby_blue);
+
+        let (_, red) = DUCK_COLOR_PRESETS[10];
+        assert_eq!(unpack("red"), red);
+        assert_ne!(unpack("red"), Color::from(css::RED));
+    }
+
+    #[test]
+    fn hex_with_or_without_hash() {
+        assert_eq!(unpack("#fff"), Color::from(Srgba::rgb_u8(255, 255, 255)));
+        assert_eq!(unpack("#ffcc00"), Color::from(Srgba::rgb_u8(255, 204, 0)));
+        assert_eq!(unpack("ffcc00"), Color::from(Srgba::rgb_u8(255, 204, 0)));
+        assert_eq!(unpack("fc0"), Color::from(Srgba::rgb_u8(255, 204, 0)));
+    }
+
+    #[test]
+    fn css_names_ignore_case_and_separators() {
+        let alice_blue = Color::from(css::ALICE_BLUE);
+        assert_eq!(unpack("aliceblue"), alice_blue);
+        assert_eq!(unpack("Alice Blue"), alice_blue);
+        assert_eq!(unpack("ALICE-BLUE"), alice_blue);
+        assert_eq!(unpack("alice_blue"), alice_blue);
+    }
+
+    #[test]
+    fn every_basic_css_name_is_known() {
+        for (name, color) in [
+            ("black", css::BLACK),
+            ("navy", css::NAVY),
+            ("silver", css::SILVER),
+            ("maroon", css::MAROON),
+            ("olive", css::OLIVE),
+            ("fuchsia", css::FUCHSIA),
+            ("cyan", css::AQUA),
+        ] {
+            assert_eq!(css_named_color(name), Some(color), "{name}");
+        }
+    }
+
+    #[test]
+    fn unknown_colors_fall_back_to_white() {
+        assert_eq!(unpack("not a colour"), Color::WHITE);
+        assert_eq!(unpack("#nope"), Color::WHITE);
+        assert_eq!(unpack(""), Color::WHITE);
+    }
+}
@@ -0,0 +1,463 @@
+//! Emotes, picked from a wheel and shown to everyone.
+//!
+//! The wheel opens with [`InputAction::EmoteWheel`] or a long press on touch
+//! screens. Picking an emote animates your duck, plays a sound and tells the
+//! server, which sends it on to the other players as `OtherPlayerEmoted`.
+//! Emotes are rate limited by [`EMOTE_COOLDOWN_SECONDS`].
+//!
+//! The duck sprite sheet has no emote frames yet, so an emote is a placeholder:
+//! the duck wobbles its transform and shows a text bubble. Swap in atlas frames
+//! in `start_emotes` and `animate_emotes` once there's art for them.
+
+use bevy::{prelude::*, ui::FocusPolicy, utils::HashMap};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    audio::{AudioChannel, PlaySfx, SfxPriority},
+    input::input_map::{action_just_pressed, gameplay_input_allowed, InputAction},
+    screens::Screen,
+    theme::prelude::*,
+    AppSet,
+};
+
+use super::{
+    death::player_alive, other_player::OtherPlayer, player::Player,
+    websocket_connect::OtherPlayerEmotedWsReceived, websocket_emote_msg::EmoteRequestEvent,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_event::<DuckEmoted>();
+    app.init_resource::<EmoteCooldown>();
+
+    app.add_systems(
+        Update,
+        (
+            (tick_emote_cooldown, tick_emotes).in_set(AppSet::TickTimers),
+            (
+                toggle_emote_wheel.run_if(action_just_pressed(InputAction::EmoteWheel)),
+                open_emote_wheel_on_long_press.run_if(gameplay_input_allowed),
+            )
+                .run_if(player_alive)
+                .in_set(AppSet::RecordInput),
+            (other_player_emoted_handler, start_emotes, animate_emotes)
+                .chain()
+                .in_set(AppSet::Update),
+        )
+            .run_if(in_state(Screen::Gameplay)),
+    );
+    app.add_systems(OnExit(Screen::Gameplay), reset_emote_cooldown);
+}
+
+/// Minimum time between two of your emotes.
+///
+/// This is only enforced by this client, which a modified one can skip. The
+/// server is expected to enforce the same limit per player, and drop `emote`
+/// messages that come in faster than this instead of sending them on.
+pub const EMOTE_COOLDOWN_SECONDS: f32 = 2.0;
+const EMOTE_SECONDS: f32 = 1.5;
+const LONG_PRESS_SECONDS: f32 = 0.6;
+/// How far a finger may drift and still count as a long press.
+const LONG_PRESS_SLOP: f32 = 20.0;
+/// In the duck's local space, where the sprite is 32 units tall.
+const EMOTE_LABEL_OFFSET: Vec2 = Vec2::new(-10.0, 22.0);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Reflect)]
+#[serde(rename_all = "snake_case")]
+pub enum Emote {
+    Wave,
+    Dance,
+    Heart,
+    Angry,
+}
+
+impl Emote {
+    pub const ALL: [Self; 4] = [Self::Wave, Self::Dance, Self::Heart, Self::Angry];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Wave => "Wave",
+            Self::Dance => "Dance",
+            Self::Heart => "Heart",
+            Self::Angry => "Angry",
+        }
+    }
+
+    /// Shown above the duck while emoting.
+    fn bubble(self) -> &'static str {
+        match self {
+            Self::Wave => "Hi!",
+            Self::Dance => "~ dance ~",
+            Self::Heart => "<3",
+            Self::Angry => ">:(",
+        }
+    }
+
+    fn bubble_color(self) -> Color {
+        match self {
+            Self::Wave => Color::WHITE,
+            Self::Dance => Color::srgb(0.6, 0.9, 1.0),
+            Self::Heart => Color::srgb(1.0, 0.45, 0.65),
+            Self::Angry => Color::srgb(1.0, 0.3, 0.2),
+        }
+    }
+
+    /// There are no emote sounds yet, so reuse existing ones at different
+    /// speeds.
+    fn sound(self) -> (&'static str, f32) {
+        match self {
+            Self::Wave => ("audio/sound_effects/duck-quack.ogg", 1.3),
+            Self::Dance => ("audio/sound_effects/boing.ogg", 1.0),
+            Self::Heart => ("audio/sound_effects/duck-quack.ogg", 0.8),
+            Self::Angry => ("audio/sound_effects/duck-quack.ogg", 0.6),
+        }
+    }
+
+    /// Rotation (radians) and scale multiplier of the duck `t` seconds in.
+    fn pose(self, t: f32) -> (f32, Vec2) {
+        match self {
+            Self::Wave => ((t * 12.0).sin() * 0.25, Vec2::ONE),
+            Self::Dance => {
+                let beat = (t * 8.0).sin();
+                (
+                    beat * 0.15,
+                    Vec2::new(1.0 - 0.08 * beat.abs(), 1.0 + 0.15 * beat.abs()),
+                )
+            }
+            Self::Heart => (0.0, Vec2::splat(1.0 + 0.12 * (t * 10.0).sin().powi(2))),
+            Self::Angry => ((t * 40.0).sin() * 0.08, Vec2::splat(1.05)),
+        }
+    }
+}
+
+/// A duck emoted. Used for both your duck and other players' ducks.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct DuckEmoted {
+    pub duck: Entity,
+    pub emote: Emote,
+}
+
+#[derive(Resource, Debug)]
+struct EmoteCooldown(Timer);
+
+impl Default for EmoteCooldown {
+    fn default() -> Self {
+        let mut timer = Timer::from_seconds(EMOTE_COOLDOWN_SECONDS, TimerMode::Once);
+        // Allow emoting right away.
+        timer.tick(timer.duration());
+        Self(timer)
+    }
+}
+
+/// An emote playing on a duck.
+#[derive(Component)]
+struct Emoting {
+    emote: Emote,
+    timer: Timer,
+    /// The duck's scale before the emote, restored when it's done.
+    base_scale: Vec3,
+}
+
+#[derive(Component)]
+struct EmoteLabel;
+
+#[derive(Component)]
+pub struct EmoteWheel;
+
+#[derive(Component)]
+struct EmoteButton(Emote);
+
+#[derive(Debug, Deserialize)]
+struct EmoteResponseData {
+    player_uuid: String,
+    emote: Emote,
+}
+
+fn tick_emote_cooldown(time: Res<Time>, mut cooldown: ResMut<EmoteCooldown>) {
+    cooldown.0.tick(time.delta());
+}
+
+fn reset_emote_cooldown(mut cooldown: ResMut<EmoteCooldown>) {
+    *cooldown = EmoteCooldown::default();
+}
+
+fn toggle_emote_wheel(mut commands: Commands, wheel_query: Query<Entity, With<EmoteWheel>>) {
+    match wheel_query.get_single() {
+        Ok(wheel) => commands.entity(wheel).despawn_recursive(),
+        Err(_) => spawn_emote_wheel(&mut commands),
+    }
+}
+
+/// Whether the emote wheel is open. Quacking is disabled meanwhile, since the
+/// gamepad button to pick an emote also quacks.
+pub fn emote_wheel_open(wheel_query: Query<(), With<EmoteWheel>>) -> bool {
+    !wheel_query.is_empty()
+}
+
+fn open_emote_wheel_on_long_press(
+    mut commands: Commands,
+    time: Res<Time>,
+    touches: Res<Touches>,
+    wheel_query: Query<(), With<EmoteWheel>>,
+    mut pressed_since: Local<HashMap<u64, f32>>,
+) {
+    let now = time.elapsed_seconds();
+    pressed_since.retain(|id, _| touches.get_pressed(*id).is_some());
+
+    // Two fingers are a pinch, not a long press.
+    if touches.iter().count() != 1 {
+        pressed_since.clear();
+        return;
+    }
+
+    for touch in touches.iter() {
+        let since = *pressed_since.entry(touch.id()).or_insert(now);
+        let held = now - since >= LONG_PRESS_SECONDS;
+        let still = touch.distance().length() <= LONG_PRESS_SLOP;
+        if held && still && wheel_query.is_empty() {
+            spawn_emote_wheel(&mut commands);
+        }
+        if held || !still {
+            // Only open once per press, and not at all when dragging.
+            pressed_since.insert(touch.id(), f32::INFINITY);
+        }
+    }
+}
+
+fn spawn_emote_wheel(commands: &mut Commands) {
+    commands
+        .spawn((
+            Name::new("Emote wheel"),
+            EmoteWheel,
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                background_color: Color::srgba(0.0, 0.0, 0.0, 0.3).into(),
+                focus_policy: FocusPolicy::Block,
+                z_index: ZIndex::Global(10),
+                ..default()
+            },
+            // Tapping outside of the buttons closes the wheel.
+            Interaction::default(),
+            StateScoped(Screen::Gameplay),
+        ))
+        .observe(close_emote_wheel)
+        .with_children(|wheel| {
+            wheel
+                .spawn(NodeBundle {
+                    style: Style {
+                        width: Val::Px(360.0),
+                        height: Val::Px(260.0),
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|ring| {
+                    // Top, right, bottom and left of the ring.
+                    let positions = [(110.0, 0.0), (220.0, 108.0), (110.0, 216.0), (0.0, 108.0)];
+                    for (emote, (left, top)) in Emote::ALL.into_iter().zip(positions) {
+                        ring.small_button(emote.label())
+                            .insert((
+                                EmoteButton(emote),
+                                Style {
+                                    position_type: PositionType::Absolute,
+                                    left: Val::Px(left),
+                                    top: Val::Px(top),
+                                    width: Val::Px(140.0),
+                                    height: Val::Px(44.0),
+                                    justify_content: JustifyContent::Center,
+                                    align_items: AlignItems::Center,
+                                    ..default()
+                                },
+                            ))
+                            .observe(pick_emote);
+                    }
+                });
+        });
+}
+
+fn close_emote_wheel(
+    trigger: Trigger<OnPress>,
+    mut commands: Commands,
+    wheel_query: Query<(), With<EmoteWheel>>,
+) {
+    if wheel_query.contains(trigger.entity()) {
+        commands.entity(trigger.entity()).despawn_recursive();
+    }
+}
+
+fn pick_emote(
+    trigger: Trigger<OnPress>,
+    mut commands: Commands,
+    button_query: Query<&EmoteButton>,
+    wheel_query: Query<Entity, With<EmoteWheel>>,
+    player_query: Query<Entity, With<Player>>,
+    mut cooldown: ResMut<EmoteCooldown>,
+    mut duck_emoted_event_writer: EventWriter<DuckEmoted>,
+    mut emote_request_event_writer: EventWriter<EmoteRequestEvent>,
+) {
+    for wheel in &wheel_query {
+        commands.entity(wheel).despawn_recursive();
+    }
+
+    let Ok(EmoteButton(emote)) = button_query.get(trigger.entity()) else {
+        return;
+    };
+    if !cooldown.0.finished() {
+        info!("Emoting too fast, wait {:.1}s", cooldown.0.remaining_secs());
+        return;
+    }
+    cooldown.0.reset();
+
+    for duck in &player_query {
+        duck_emoted_event_writer.send(DuckEmoted {
+            duck,
+            emote: *emote,
+        });
+    }
+    emote_request_event_writer.send(EmoteRequestEvent { emote: *emote });
+}
+
+fn other_player_emoted_handler(
+    mut event_reader: EventReader<OtherPlayerEmotedWsReceived>,
+    other_players: Query<(Entity, &Name), With<OtherPlayer>>,
+    mut duck_emoted_event_writer: EventWriter<DuckEmoted>,
+) {
+    for e in event_reader.read() {
+        let data: EmoteResponseData = match serde_json::from_value(e.data.clone()) {
+            Ok(data) => data,
+            Err(op) => {
+                info!("Failed to parse incoming websocket message: {}", op);
+                continue;
+            }
+        };
+
+        for (entity, name) in other_players.iter() {
+            if name.to_string() == data.player_uuid {
+                duck_emoted_event_writer.send(DuckEmoted {
+                    duck: entity,
+                    emote: data.emote,
+                });
+            }
+        }
+    }
+}
+
+/// Starts the placeholder emote animation on the duck: see the module docs.
+fn start_emotes(
+    mut commands: Commands,
+    mut events: EventReader<DuckEmoted>,
+    asset_server: Res<AssetServer>,
+    mut play_sfx: EventWriter<PlaySfx>,
+    mut duck_query: Query<(
+        &mut Transform,
+        &GlobalTransform,
+        Option<&Emoting>,
+        Option<&Children>,
+    )>,
+    label_query: Query<(), With<EmoteLabel>>,
+    player_query: Query<(), With<Player>>,
+) {
+    for event in events.read() {
+        let Ok((mut transform, global_transform, emoting, children)) =
+            duck_query.get_mut(event.duck)
+        else {
+            continue;
+        };
+
+        // A new emote replaces the one that's playing.
+        let base_scale = match emoting {
+            Some(emoting) => emoting.base_scale,
+            None => transform.scale,
+        };
+        transform.rotation = Quat::IDENTITY;
+        transform.scale = base_scale;
+        for child in children.into_iter().flatten() {
+            if label_query.contains(*child) {
+                commands.entity(*child).despawn_recursive();
+            }
+        }
+
+        commands
+            .entity(event.duck)
+            .insert(Emoting {
+                emote: event.emote,
+                timer: Timer::from_seconds(EMOTE_SECONDS, TimerMode::Once),
+                base_scale,
+            })
+            .with_children(|parent| {
+                parent.spawn((
+                    Name::new("Emote label"),
+                    EmoteLabel,
+                    Text2dBundle {
+                        text: Text::from_section(
+                            event.emote.bubble(),
+                            TextStyle {
+                                font: asset_server.load("FiraSans-Bold.ttf"),
+                                font_size: 25.0,
+                                color: event.emote.bubble_color(),
+                            },
+                        ),
+                        transform: Transform {
+                            translation: EMOTE_LABEL_OFFSET.extend(2.0),
+                            // Cancel out the duck's 4x scale, like the name label.
+                            scale: Vec3::new(0.25, 0.25, 1.0),
+                            ..default()
+                        },
+                        ..default()
+                    },
+                ));
+            });
+
+        let (sound, speed) = event.emote.sound();
+        let source = asset_server.load(sound);
+        // Other players' emotes come from where their duck is.
+        let sound = if player_query.contains(event.duck) {
+            PlaySfx::new(AudioChannel::Sfx, source)
+        } else {
+            PlaySfx::new(AudioChannel::RemotePlayers, source)
+                .at(global_transform.translation().truncate())
+                .following(event.duck)
+        };
+        play_sfx.send(sound.with_speed(speed).with_priority(SfxPriority::High));
+    }
+}
+
+fn tick_emotes(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut duck_query: Query<(Entity, &mut Emoting, &mut Transform, Option<&Children>)>,
+    label_query: Query<(), With<EmoteLabel>>,
+) {
+    for (entity, mut emoting, mut transform, children) in &mut duck_query {
+        emoting.timer.tick(time.delta());
+        if !emoting.timer.finished() {
+            continue;
+        }
+
+        transform.rotation = Quat::IDENTITY;
+        transform.scale = emoting.base_scale;
+        commands.entity(entity).remove::<Emoting>();
+        for child in children.into_iter().flatten() {
+            if label_query.contains(*child) {
+                commands.entity(*child).despawn_recursive();
+            }
+        }
+    }
+}
+
+fn animate_emotes(mut duck_query: Query<(&Emoting, &mut Transform)>) {
+    for (emoting, mut transform) in &mut duck_query {
+        if emoting.timer.finished() {
+            continue;
+        }
+        let (angle, scale) = emoting.emote.pose(emoting.timer.elapsed_secs());
+        transform.rotation = Quat::from_rotation_z(angle);
+        transform.scale = (emoting.base_scale.truncate() * scale).extend(emoting.base_scale.z);
+    }
+}
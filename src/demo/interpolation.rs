@@ -0,0 +1,49 @@
+//! Smoothing for positions that come in over the network.
+//!
+//! Remote ducks and NPCs only get a new position every now and then. Instead
+//! of jumping there, they ease towards their latest [`NetworkPosition`] each
+//! frame, unless it's so far away that it must have been a teleport.
+
+use bevy::prelude::*;
+
+use crate::AppSet;
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<NetworkPosition>();
+    app.add_systems(Update, interpolate_network_positions.in_set(AppSet::Update));
+}
+
+/// How quickly entities catch up with their [`NetworkPosition`]. Higher is
+/// snappier, lower is smoother.
+const INTERPOLATION_RATE: f32 = 12.0;
+/// Anything further away than this is snapped to instead, e.g. a respawn.
+const SNAP_DISTANCE: f32 = 400.0;
+
+/// The latest position the server gave for this entity.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Reflect)]
+#[reflect(Component)]
+pub struct NetworkPosition(pub Vec2);
+
+fn interpolate_network_positions(
+    time: Res<Time>,
+    mut query: Query<(&NetworkPosition, &mut Transform)>,
+) {
+    // Frame rate independent exponential smoothing.
+    let t = 1.0 - (-INTERPOLATION_RATE * time.delta_seconds()).exp();
+
+    for (target, mut transform) in &mut query {
+        let current = transform.translation.truncate();
+        if current == target.0 {
+            continue;
+        }
+        let distance = current.distance(target.0);
+        // Snap when it's close enough, or too far away to have walked there.
+        let next = if (0.5..=SNAP_DISTANCE).contains(&distance) {
+            current.lerp(target.0, t)
+        } else {
+            target.0
+        };
+        transform.translation.x = next.x;
+        transform.translation.y = next.y;
+    }
+}
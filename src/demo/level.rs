@@ -1,19 +1,298 @@
-// Yeah, basically not even using this file anymore, but it 
-// is kinda nice to have this example of a custom command
-// bc tbh I still don't really understand when to use those...
+//! The level: background and foreground layers, props, colliders, spawn areas, water zones and
+//! world bounds, loaded from a `.level.ron` file in `assets/levels`.
 
-use bevy::{app::App, prelude::World};
+use bevy::{
+    asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext},
+    prelude::*,
+};
+use serde::Deserialize;
+use thiserror::Error;
 
-pub(super) fn plugin(_app: &mut App) {
+use crate::{
+    asset_tracking::LoadResource,
+    demo::{
+        background::{background_layer, foreground_layer, RepeatingLayer},
+        collision::{Collider, CollisionLayer, PlacedCollider},
+    },
+    screens::Screen,
+};
 
+pub(super) fn plugin(app: &mut App) {
+    app.init_asset::<Level>();
+    app.init_asset_loader::<LevelLoader>();
+    app.load_resource::<LevelAssets>();
+    app.init_resource::<LevelBounds>();
+    app.register_type::<LevelProp>();
+    app.register_type::<SpawnArea>();
+    app.register_type::<WaterZone>();
 }
 
+#[derive(Resource, Asset, Reflect, Clone)]
+pub struct LevelAssets {
+    #[dependency]
+    pub level: Handle<Level>,
+}
+
+impl LevelAssets {
+    pub const PATH_LEVEL: &'static str = "levels/pond.level.ron";
+}
+
+impl FromWorld for LevelAssets {
+    fn from_world(world: &mut World) -> Self {
+        let assets = world.resource::<AssetServer>();
+        Self {
+            level: assets.load(LevelAssets::PATH_LEVEL),
+        }
+    }
+}
+
+/// A loaded level, with the images it references already loaded.
+#[derive(Asset, TypePath, Debug, Clone)]
+pub struct Level {
+    /// Ducks can't leave this area.
+    pub bounds: Rect,
+    pub background_layers: Vec<LevelSprite>,
+    /// Drawn over the ducks, so they can walk behind them.
+    pub foreground_layers: Vec<LevelSprite>,
+    pub props: Vec<LevelSprite>,
+    /// Where the server may place joining ducks. Kept here so that the level
+    /// file is the single source of truth for both sides.
+    pub spawn_areas: Vec<Rect>,
+    pub water_zones: Vec<Rect>,
+    /// Every image used by the level, so that loading waits for them.
+    #[dependency]
+    images: Vec<Handle<Image>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct LevelSprite {
+    pub image: Handle<Image>,
+    pub position: Vec2,
+    pub z: f32,
+    pub scale: f32,
+    pub collider: Option<Collider>,
+    pub parallax: f32,
+    pub repeat: bool,
+}
+
+/// The on-disk format of a [`Level`].
+#[derive(Deserialize)]
+struct LevelFile {
+    bounds: Rect,
+    #[serde(default)]
+    background_layers: Vec<LevelSpriteFile>,
+    #[serde(default)]
+    foreground_layers: Vec<LevelSpriteFile>,
+    #[serde(default)]
+    props: Vec<LevelSpriteFile>,
+    #[serde(default)]
+    spawn_areas: Vec<Rect>,
+    #[serde(default)]
+    water_zones: Vec<Rect>,
+}
+
+#[derive(Deserialize)]
+struct LevelSpriteFile {
+    image: String,
+    position: Vec2,
+    #[serde(default)]
+    z: f32,
+    #[serde(default = "default_scale")]
+    scale: f32,
+    /// In world units, independent of `scale`.
+    #[serde(default)]
+    collider: Option<Collider>,
+    /// Layers only. How much the layer moves with the world: 1 sticks to the
+    /// world, 0 sticks to the screen, and above 1 is closer than the ducks.
+    #[serde(default = "default_parallax")]
+    parallax: f32,
+    /// Layers only. Whether the image repeats to fill the view.
+    #[serde(default)]
+    repeat: bool,
+}
+
+fn default_scale() -> f32 {
+    1.0
+}
+
+fn default_parallax() -> f32 {
+    1.0
+}
+
+#[derive(Default)]
+struct LevelLoader;
+
+#[derive(Error, Debug)]
+enum LevelLoaderError {
+    #[error("IO")]
+    Io(#[from] std::io::Error),
+    #[error("RON")]
+    Ron(#[from] ron::error::SpannedError),
+}
+
+impl AssetLoader for LevelLoader {
+    type Asset = Level;
+    type Settings = ();
+    type Error = LevelLoaderError;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        _settings: &'a (),
+        load_context: &'a mut LoadContext<'_>,
+    ) -> Result<Level, LevelLoaderError> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        let file: LevelFile = ron::de::from_bytes(&bytes)?;
+
+        let mut images = Vec::new();
+        let mut load_sprites = |sprites: Vec<LevelSpriteFile>| -> Vec<LevelSprite> {
+            sprites
+                .into_iter()
+                .map(|sprite| {
+                    let image: Handle<Image> = load_context.load(sprite.image);
+                    images.push(image.clone());
+                    LevelSprite {
+                        image,
+                        position: sprite.position,
+                        z: sprite.z,
+                        scale: sprite.scale,
+                        collider: sprite.collider,
+                        parallax: sprite.parallax,
+                        repeat: sprite.repeat,
+                    }
+                })
+                .collect()
+        };
+        let background_layers = load_sprites(file.background_layers);
+        let foreground_layers = load_sprites(file.foreground_layers);
+        let props = load_sprites(file.props);
+
+        Ok(Level {
+            bounds: file.bounds,
+            background_layers,
+            foreground_layers,
+            props,
+            spawn_areas: file.spawn_areas,
+            water_zones: file.water_zones,
+            images,
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["level.ron"]
+    }
+}
+
+/// The area ducks and the camera are kept inside of.
+#[derive(Resource, Debug, Clone, Copy, PartialEq)]
+pub struct LevelBounds(pub Rect);
+
+impl Default for LevelBounds {
+    fn default() -> Self {
+        Self(Rect::new(-1000., -1000., 1000., 1000.))
+    }
+}
+
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Default, Reflect)]
+#[reflect(Component)]
+pub struct LevelProp;
+
+#[derive(Component, Debug, Clone, Copy, PartialEq, Reflect)]
+#[reflect(Component)]
+pub struct SpawnArea(pub Rect);
+
+#[derive(Component, Debug, Clone, Copy, PartialEq, Reflect)]
+#[reflect(Component)]
+pub struct WaterZone(pub Rect);
+
+const WATER_COLOR: Color = Color::srgba(0.25, 0.55, 0.9, 0.45);
+const WATER_Z: f32 = 2.0;
+
 /// A [`Command`] to spawn the level.
 /// Functions that accept only `&mut World` as their parameter implement [`Command`].
 /// We use this style when a command requires no configuration.
-pub fn spawn_level(_world: &mut World) {
-    // The only thing we have in our level is a player,
-    // but add things like walls etc. here.
-    
-    // SpawnPlayer { max_speed: 400.0 }.apply(world);
+pub fn spawn_level(world: &mut World) {
+    let Some(level) = world
+        .get_resource::<LevelAssets>()
+        .and_then(|level_assets| world.resource::<Assets<Level>>().get(&level_assets.level))
+        .cloned()
+    else {
+        warn!("Tried to spawn the level before it was loaded");
+        return;
+    };
+
+    world.insert_resource(LevelBounds(level.bounds));
+    world.insert_resource(CollisionLayer {
+        colliders: level
+            .props
+            .iter()
+            .filter_map(|prop| {
+                prop.collider.map(|collider| PlacedCollider {
+                    position: prop.position,
+                    collider,
+                })
+            })
+            .collect(),
+    });
+
+    for layer in &level.background_layers {
+        let mut entity = world.spawn((background_layer(layer), StateScoped(Screen::Gameplay)));
+        if layer.repeat {
+            entity.insert(RepeatingLayer::bundle());
+        }
+    }
+
+    for layer in &level.foreground_layers {
+        let mut entity = world.spawn((foreground_layer(layer), StateScoped(Screen::Gameplay)));
+        if layer.repeat {
+            entity.insert(RepeatingLayer::bundle());
+        }
+    }
+
+    for prop in &level.props {
+        let mut entity = world.spawn((
+            Name::new("Prop"),
+            LevelProp,
+            SpriteBundle {
+                texture: prop.image.clone(),
+                transform: Transform {
+                    translation: prop.position.extend(prop.z),
+                    scale: Vec2::splat(prop.scale).extend(1.0),
+                    ..default()
+                },
+                ..default()
+            },
+            StateScoped(Screen::Gameplay),
+        ));
+        if let Some(collider) = prop.collider {
+            entity.insert(collider);
+        }
+    }
+
+    for area in &level.spawn_areas {
+        world.spawn((
+            Name::new("Spawn area"),
+            SpawnArea(*area),
+            SpatialBundle::from_transform(Transform::from_translation(area.center().extend(0.0))),
+            StateScoped(Screen::Gameplay),
+        ));
+    }
+
+    for zone in &level.water_zones {
+        world.spawn((
+            Name::new("Water zone"),
+            WaterZone(*zone),
+            SpriteBundle {
+                sprite: Sprite {
+                    color: WATER_COLOR,
+                    custom_size: Some(zone.size()),
+                    ..default()
+                },
+                transform: Transform::from_translation(zone.center().extend(WATER_Z)),
+                ..default()
+            },
+            StateScoped(Screen::Gameplay),
+        ));
+    }
 }
@@ -0,0 +1,332 @@
+//! A minimap in the corner of the screen.
+//!
+//! It shows the outline of the level and a dot for every entity with a
+//! [`MinimapIcon`]: your duck, the other ducks and the crackers. It can be
+//! toggled with [`InputAction::ToggleMinimap`], and clicking or tapping on it
+//! pings that spot, both on the minimap and in the world.
+
+use bevy::{
+    prelude::*,
+    ui::{FocusPolicy, RelativeCursorPosition},
+};
+
+use crate::{
+    input::input_map::{action_just_pressed, InputAction},
+    screens::Screen,
+    AppSet,
+};
+
+use super::level::LevelBounds;
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<MinimapIcon>();
+    app.init_resource::<ShowMinimap>();
+
+    app.add_systems(OnEnter(Screen::Gameplay), spawn_minimap);
+    app.add_systems(
+        Update,
+        (
+            toggle_minimap.run_if(action_just_pressed(InputAction::ToggleMinimap)),
+            show_minimap.run_if(resource_changed::<ShowMinimap>),
+            sync_markers,
+            update_markers,
+            ping_on_click,
+        )
+            .chain()
+            .in_set(AppSet::Update)
+            .run_if(in_state(Screen::Gameplay)),
+    );
+    app.add_systems(
+        Update,
+        (tick_pings.in_set(AppSet::TickTimers), draw_world_pings)
+            .run_if(in_state(Screen::Gameplay)),
+    );
+}
+
+const MINIMAP_SIZE: f32 = 180.0;
+const MINIMAP_BACKGROUND: Color = Color::srgba(0.0, 0.0, 0.0, 0.5);
+const PING_COLOR: Color = Color::srgb(1.0, 0.85, 0.2);
+const PING_SECONDS: f32 = 1.5;
+/// The radius a ping grows to in the world.
+const PING_WORLD_RADIUS: f32 = 120.0;
+/// The size a ping grows to on the minimap.
+const PING_MINIMAP_SIZE: f32 = 30.0;
+
+/// Whether the minimap is shown. Kept between games.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ShowMinimap(pub bool);
+
+impl Default for ShowMinimap {
+    fn default() -> Self {
+        Self(true)
+    }
+}
+
+/// Shows an entity on the minimap as a dot.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Reflect)]
+#[reflect(Component)]
+pub struct MinimapIcon {
+    pub color: Color,
+    /// Diameter in pixels.
+    pub size: f32,
+}
+
+impl MinimapIcon {
+    pub const YOU: Self = Self {
+        color: Color::WHITE,
+        size: 12.0,
+    };
+    pub const CRACKER: Self = Self {
+        color: Color::srgb(0.85, 0.6, 0.25),
+        size: 10.0,
+    };
+    pub const THREAT: Self = Self {
+        color: Color::srgb(0.95, 0.2, 0.15),
+        size: 12.0,
+    };
+
+    pub fn other_duck(color: Color) -> Self {
+        Self { color, size: 8.0 }
+    }
+}
+
+#[derive(Component)]
+struct Minimap;
+
+/// The part of the minimap that represents the level.
+#[derive(Component)]
+struct MinimapArea;
+
+/// A dot on the minimap for the entity with the [`MinimapIcon`].
+#[derive(Component)]
+struct MinimapMarker(Entity);
+
+/// A spot that was pinged. Exists both as a UI node on the minimap and as an
+/// entity in the world.
+#[derive(Component)]
+struct Ping(Timer);
+
+#[derive(Component)]
+struct WorldPing;
+
+fn spawn_minimap(mut commands: Commands, show_minimap: Res<ShowMinimap>) {
+    commands
+        .spawn((
+            Name::new("Minimap"),
+            Minimap,
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    left: Val::Percent(3.),
+                    bottom: Val::Percent(8.),
+                    width: Val::Px(MINIMAP_SIZE),
+                    height: Val::Px(MINIMAP_SIZE),
+                    border: UiRect::all(Val::Px(2.0)),
+                    ..default()
+                },
+                background_color: MINIMAP_BACKGROUND.into(),
+                border_color: Color::WHITE.into(),
+                visibility: minimap_visibility(*show_minimap),
+                ..default()
+            },
+            StateScoped(Screen::Gameplay),
+        ))
+        .with_children(|minimap| {
+            minimap.spawn((
+                Name::new("Minimap area"),
+                MinimapArea,
+                NodeBundle {
+                    style: Style {
+                        width: Val::Percent(100.0),
+                        height: Val::Percent(100.0),
+                        overflow: Overflow::clip(),
+                        ..default()
+                    },
+                    // Don't let clicks through to the touch joystick.
+                    focus_policy: FocusPolicy::Block,
+                    ..default()
+                },
+                Interaction::default(),
+                RelativeCursorPosition::default(),
+            ));
+        });
+}
+
+fn minimap_visibility(show_minimap: ShowMinimap) -> Visibility {
+    if show_minimap.0 {
+        Visibility::Inherited
+    } else {
+        Visibility::Hidden
+    }
+}
+
+fn toggle_minimap(mut show_minimap: ResMut<ShowMinimap>) {
+    show_minimap.0 = !show_minimap.0;
+}
+
+fn show_minimap(
+    show_minimap: Res<ShowMinimap>,
+    mut minimap_query: Query<&mut Visibility, With<Minimap>>,
+) {
+    for mut visibility in &mut minimap_query {
+        *visibility = minimap_visibility(*show_minimap);
+    }
+}
+
+/// Spawn markers for new icons and despawn the ones whose entity is gone.
+fn sync_markers(
+    mut commands: Commands,
+    area_query: Query<Entity, With<MinimapArea>>,
+    icon_query: Query<(Entity, &MinimapIcon)>,
+    marker_query: Query<(Entity, &MinimapMarker)>,
+) {
+    let Ok(area) = area_query.get_single() else {
+        return;
+    };
+
+    for (marker, MinimapMarker(target)) in &marker_query {
+        if !icon_query.contains(*target) {
+            commands.entity(marker).despawn_recursive();
+        }
+    }
+
+    for (target, icon) in &icon_query {
+        if marker_query.iter().any(|(_, marker)| marker.0 == target) {
+            continue;
+        }
+        commands.entity(area).with_children(|area| {
+            area.spawn((
+                Name::new("Minimap marker"),
+                MinimapMarker(target),
+                NodeBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        width: Val::Px(icon.size),
+                        height: Val::Px(icon.size),
+                        // Center the dot on its position.
+                        margin: UiRect::all(Val::Px(-icon.size / 2.0)),
+                        ..default()
+                    },
+                    background_color: icon.color.into(),
+                    border_radius: BorderRadius::MAX,
+                    // Draw your own duck on top of everything else.
+                    z_index: ZIndex::Local(if *icon == MinimapIcon::YOU { 1 } else { 0 }),
+                    ..default()
+                },
+            ));
+        });
+    }
+}
+
+fn update_markers(
+    bounds: Res<LevelBounds>,
+    icon_query: Query<(&GlobalTransform, &MinimapIcon)>,
+    mut marker_query: Query<(&MinimapMarker, &mut Style, &mut BackgroundColor)>,
+) {
+    for (marker, mut style, mut background) in &mut marker_query {
+        let Ok((transform, icon)) = icon_query.get(marker.0) else {
+            continue;
+        };
+        let relative = world_to_minimap(transform.translation().truncate(), bounds.0);
+        style.left = Val::Percent(relative.x * 100.0);
+        style.top = Val::Percent(relative.y * 100.0);
+        background.0 = icon.color;
+    }
+}
+
+fn ping_on_click(
+    mut commands: Commands,
+    bounds: Res<LevelBounds>,
+    area_query: Query<(Entity, &Interaction, &RelativeCursorPosition), Changed<Interaction>>,
+) {
+    for (area, interaction, cursor) in &area_query {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        let Some(relative) = cursor.normalized else {
+            continue;
+        };
+
+        commands.entity(area).with_children(|area| {
+            area.spawn((
+                Name::new("Minimap ping"),
+                Ping(Timer::from_seconds(PING_SECONDS, TimerMode::Once)),
+                NodeBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        left: Val::Percent(relative.x * 100.0),
+                        top: Val::Percent(relative.y * 100.0),
+                        border: UiRect::all(Val::Px(2.0)),
+                        ..default()
+                    },
+                    border_color: PING_COLOR.into(),
+                    border_radius: BorderRadius::MAX,
+                    ..default()
+                },
+            ));
+        });
+
+        commands.spawn((
+            Name::new("World ping"),
+            Ping(Timer::from_seconds(PING_SECONDS, TimerMode::Once)),
+            WorldPing,
+            SpatialBundle::from_transform(Transform::from_translation(
+                minimap_to_world(relative, bounds.0).extend(0.0),
+            )),
+            StateScoped(Screen::Gameplay),
+        ));
+    }
+}
+
+/// Grow the minimap pings and despawn all pings once they're done.
+fn tick_pings(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut ping_query: Query<(
+        Entity,
+        &mut Ping,
+        Option<&mut Style>,
+        Option<&mut BorderColor>,
+    )>,
+) {
+    for (entity, mut ping, style, border) in &mut ping_query {
+        ping.0.tick(time.delta());
+        if ping.0.finished() {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+
+        let progress = ping.0.fraction();
+        if let Some(mut style) = style {
+            let size = PING_MINIMAP_SIZE * progress;
+            style.width = Val::Px(size);
+            style.height = Val::Px(size);
+            style.margin = UiRect::all(Val::Px(-size / 2.0));
+        }
+        if let Some(mut border) = border {
+            border.0 = PING_COLOR.with_alpha(1.0 - progress);
+        }
+    }
+}
+
+fn draw_world_pings(mut gizmos: Gizmos, ping_query: Query<(&Ping, &Transform), With<WorldPing>>) {
+    for (ping, transform) in &ping_query {
+        let progress = ping.0.fraction();
+        gizmos.circle_2d(
+            transform.translation.truncate(),
+            PING_WORLD_RADIUS * progress,
+            PING_COLOR.with_alpha(1.0 - progress),
+        );
+    }
+}
+
+/// Where a world position is on the minimap, from (0, 0) at the top left to
+/// (1, 1) at the bottom right.
+fn world_to_minimap(position: Vec2, bounds: Rect) -> Vec2 {
+    let relative = (position - bounds.min) / bounds.size();
+    Vec2::new(relative.x, 1.0 - relative.y).clamp(Vec2::ZERO, Vec2::ONE)
+}
+
+fn minimap_to_world(relative: Vec2, bounds: Rect) -> Vec2 {
+    bounds.min + Vec2::new(relative.x, 1.0 - relative.y) * bounds.size()
+}
@@ -1,31 +1,74 @@
 use bevy::prelude::*;
 
 pub mod level;
+pub mod minimap;
 mod movement;
 pub mod player;
 pub mod player_animation;
+pub mod quack;
+pub mod emote;
+pub mod chat;
 pub mod other_player;
 pub mod other_player_animation;
 pub mod cracker;
+pub mod death;
+pub mod dash;
 pub mod score;
 pub mod background;
+pub mod buffs;
+pub mod camera;
+pub mod collision;
+pub mod duck_color;
+pub mod day_night;
+pub mod interpolation;
+pub mod npc;
 pub mod websocket_connect;
 pub mod websocket_join_msg;
 pub mod websocket_move_msg;
+pub mod websocket_interact_msg;
+pub mod websocket_emote_msg;
+pub mod websocket_chat_msg;
+pub mod websocket_leave_msg;
 
 pub(super) fn plugin(app: &mut App) {
     app.add_plugins((
         movement::plugin,
         level::plugin,
+        minimap::plugin,
         player::plugin,
         player_animation::plugin,
         other_player::plugin,
         other_player_animation::plugin,
         cracker::plugin,
+        death::plugin,
         score::plugin,
         background::plugin,
+        buffs::plugin,
+        camera::plugin,
+        collision::plugin,
+        duck_color::plugin,
+    ));
+
+    // Things your duck can do.
+    app.add_plugins(dash::plugin);
+
+    // Ways for ducks to talk to each other.
+    app.add_plugins((quack::plugin, emote::plugin, chat::plugin));
+
+    // Things the server moves around.
+    app.add_plugins((interpolation::plugin, npc::plugin));
+
+    // The world around the ducks.
+    app.add_plugins(day_night::plugin);
+
+    // Networking.
+    app.add_plugins((
         websocket_connect::plugin,
         websocket_join_msg::plugin,
         websocket_move_msg::plugin,
+        websocket_interact_msg::plugin,
+        websocket_emote_msg::plugin,
+        websocket_chat_msg::plugin,
+        websocket_leave_msg::plugin,
     ));
 }
@@ -7,7 +7,10 @@
 //!   This is done in the `player` module, as it is specific to the player
 //!   character.
 //! - Apply movement based on [`MovementController`] intent and maximum speed.
-//! - Wrap the character within the window.
+//!   In a [`WaterZone`] the character is slower and glides, speeding up and
+//!   slowing down gradually.
+//! - Push characters with a [`Collider`] out of the level's [`CollisionLayer`].
+//! - Keep the character within the [`LevelBounds`].
 //!
 //! Note that the implementation used here is limited for demonstration
 //! purposes. If you want to move the player in a smoother way,
@@ -17,16 +20,15 @@ use bevy::prelude::*;
 
 use crate::AppSet;
 
-use super::websocket_move_msg::MoveRequestEvent;
-
-pub const MIN_X_POS: f32 = -1000.;
-pub const MIN_Y_POS: f32 = -1000.;
-
-pub const MAX_X_POS: f32 = 1000.;
-pub const MAX_Y_POS: f32 = 1000.;
+use super::{
+    collision::{Collider, CollisionLayer},
+    level::{LevelBounds, WaterZone},
+    websocket_move_msg::MoveRequestEvent,
+};
 
 pub(super) fn plugin(app: &mut App) {
     app.register_type::<MovementController>();
+    app.add_event::<SwimmingChanged>();
 
     app.add_systems(Update, apply_movement.chain().in_set(AppSet::Update));
 }
@@ -44,6 +46,36 @@ pub struct MovementController {
     /// 1 world unit = 1 pixel when using the default 2D camera and no physics
     /// engine.
     pub max_speed: f32,
+
+    /// The velocity the character moved with last frame, in world units per
+    /// second.
+    pub velocity: Vec2,
+
+    /// Whether the character is in a [`WaterZone`].
+    pub swimming: bool,
+
+    /// Whether the character is dashing, moving [`DASH_SPEED_MULTIPLIER`]
+    /// times faster.
+    pub dashing: bool,
+}
+
+/// How much faster characters move while dashing.
+pub const DASH_SPEED_MULTIPLIER: f32 = 2.5;
+
+/// How fast characters swim, relative to their `max_speed`.
+const SWIM_SPEED_FACTOR: f32 = 0.6;
+/// How quickly characters speed up in the water, in world units per second
+/// squared.
+const SWIM_ACCELERATION: f32 = 600.0;
+/// How quickly characters slow down in the water once they stop paddling. Lower
+/// than [`SWIM_ACCELERATION`], so they glide.
+const SWIM_DECELERATION: f32 = 250.0;
+
+/// Sent when a character gets into or out of the water.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct SwimmingChanged {
+    pub entity: Entity,
+    pub swimming: bool,
 }
 
 impl Default for MovementController {
@@ -52,64 +84,72 @@ impl Default for MovementController {
             intent: Vec2::ZERO,
             // 400 pixels per second is a nice default, but we can still vary this per character.
             max_speed: 400.0,
+            velocity: Vec2::ZERO,
+            swimming: false,
+            dashing: false,
         }
     }
 }
 
 fn apply_movement(
     time: Res<Time>,
-    mut param_set: ParamSet<(
-        Query<(&MovementController, &mut Transform)>,
-        Query<&mut Transform, With<Camera>>,
+    bounds: Res<LevelBounds>,
+    collision_layer: Res<CollisionLayer>,
+    water_query: Query<&WaterZone>,
+    mut movement_query: Query<(
+        Entity,
+        &mut MovementController,
+        Option<&Collider>,
+        &mut Transform,
     )>,
-    mut move_request_event_writer: EventWriter<MoveRequestEvent>
+    mut move_request_event_writer: EventWriter<MoveRequestEvent>,
+    mut swimming_changed_event_writer: EventWriter<SwimmingChanged>,
 ) {
-    let mut translation = Vec3 {
-        x: 0.,
-        y: 0.,
-        z: 0.,
-    };
-
-    for (controller, mut transform) in &mut param_set.p0().iter_mut() {
-        let velocity = controller.max_speed * controller.intent;
-        transform.translation += velocity.extend(0.0) * time.delta_seconds();
-        translation = velocity.extend(0.0) * time.delta_seconds();
-
-        if transform.translation.x < MIN_X_POS {
-            transform.translation.x = MIN_X_POS
-        }
-        if transform.translation.y < MIN_Y_POS {
-            transform.translation.y = MIN_Y_POS
+    for (entity, mut controller, collider, mut transform) in &mut movement_query {
+        let start = transform.translation.truncate();
+
+        let swimming = water_query.iter().any(|zone| zone.0.contains(start));
+        if swimming != controller.swimming {
+            controller.swimming = swimming;
+            swimming_changed_event_writer.send(SwimmingChanged { entity, swimming });
         }
-        if transform.translation.x > MAX_X_POS {
-            transform.translation.x = MAX_X_POS
+
+        let mut target_velocity = controller.max_speed * controller.intent;
+        if controller.dashing {
+            target_velocity *= DASH_SPEED_MULTIPLIER;
         }
-        if transform.translation.y > MAX_Y_POS {
-            transform.translation.y = MAX_Y_POS
+        controller.velocity = if swimming {
+            let target_velocity = target_velocity * SWIM_SPEED_FACTOR;
+            let rate = if controller.intent == Vec2::ZERO {
+                SWIM_DECELERATION
+            } else {
+                SWIM_ACCELERATION
+            };
+            let change = (target_velocity - controller.velocity)
+                .clamp_length_max(rate * time.delta_seconds());
+            controller.velocity + change
+        } else {
+            target_velocity
+        };
+
+        let mut position = start + controller.velocity * time.delta_seconds();
+
+        if let Some(Collider::Circle { radius }) = collider {
+            position = collision_layer.resolve_circle(position, *radius);
         }
-    }
+        position = position.clamp(bounds.0.min, bounds.0.max);
+
+        transform.translation = position.extend(transform.translation.z);
 
-    // No need to ping server and update camera if no change
-    if !(translation.x == 0. && translation.y == 0.) {   
-        for mut camera in &mut param_set.p1().iter_mut() {
-            camera.translation += translation;
-
-            // Keep camera movement within bounds
-            if camera.translation.x < MIN_X_POS {
-                camera.translation.x = MIN_X_POS
-            }
-            if camera.translation.y < MIN_Y_POS {
-                camera.translation.y = MIN_Y_POS
-            }
-            if camera.translation.x > MAX_X_POS {
-                camera.translation.x = MAX_X_POS
-            }
-            if camera.translation.y > MAX_Y_POS {
-                camera.translation.y = MAX_Y_POS
-            }
-            
-            // send movement request to ws server
-            move_request_event_writer.send(MoveRequestEvent(translation.x, translation.y));
+        // No need to ping server if no change. Only report how far we actually
+        // got, so the server doesn't move us into walls.
+        let translation = position - start;
+        if translation != Vec2::ZERO {
+            move_request_event_writer.send(MoveRequestEvent(
+                translation.x,
+                translation.y,
+                controller.dashing,
+            ));
         }
     }
 }
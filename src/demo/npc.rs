@@ -0,0 +1,562 @@
+//! Non-player characters run by the server, like predators.
+//!
+//! The server spawns, moves and despawns each [`Npc`] by id. Their positions
+//! are smoothed with a [`NetworkPosition`], the same as other players' ducks.
+//! There's no art for them yet, so each [`NpcKind`] is drawn from a few
+//! simple shapes.
+//!
+//! When one gets close to your duck, a warning pops up above it, and the
+//! NPC's shadow grows darker. Hawks also swoop down as they close in.
+
+use bevy::{
+    prelude::*,
+    sprite::{MaterialMesh2dBundle, Mesh2dHandle},
+};
+use serde::Deserialize;
+
+use crate::{screens::Screen, AppSet};
+
+use super::{
+    interpolation::NetworkPosition,
+    minimap::MinimapIcon,
+    player::Player,
+    websocket_connect::{DespawnNpcBevyEvent, MoveNpcBevyEvent, SpawnNpcBevyEvent},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<Npc>();
+    app.init_resource::<NpcAssets>();
+
+    app.add_systems(
+        Update,
+        (
+            (
+                listen_for_spawn_npc_bevy_event,
+                listen_for_move_npc_bevy_event,
+                listen_for_despawn_npc_bevy_event,
+            )
+                .chain(),
+            (
+                face_movement,
+                animate_hawk_wings,
+                show_approach,
+                update_threat_warning,
+            )
+                .in_set(AppSet::Update)
+                .run_if(in_state(Screen::Gameplay)),
+        ),
+    );
+}
+
+/// An NPC is a threat once it's this close to your duck.
+pub const NPC_WARNING_RADIUS: f32 = 450.0;
+/// How high a hawk flies, as the distance to its shadow, when it's not
+/// swooping at you.
+const HAWK_ALTITUDE: f32 = 130.0;
+const HAWK_SWOOP_ALTITUDE: f32 = 40.0;
+/// Shadows sit just above the ground props.
+const SHADOW_Z: f32 = 4.0;
+/// In the duck's local space, where the sprite is 32 units tall.
+const WARNING_OFFSET: Vec2 = Vec2::new(-18.0, 12.0);
+const WARNING_COLOR: Color = Color::srgb(1.0, 0.2, 0.15);
+
+/// A server-controlled character, keyed by the id the server gave it.
+#[derive(Component, Debug, Clone, PartialEq, Eq, Reflect)]
+#[reflect(Component)]
+pub struct Npc {
+    pub id: String,
+    pub kind: NpcKind,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Reflect)]
+#[serde(rename_all = "snake_case")]
+pub enum NpcKind {
+    Fox,
+    Hawk,
+}
+
+impl NpcKind {
+    fn label(self) -> &'static str {
+        match self {
+            Self::Fox => "Fox",
+            Self::Hawk => "Hawk",
+        }
+    }
+
+    /// Hawks fly over everything, foxes walk among the ducks.
+    fn z(self) -> f32 {
+        match self {
+            Self::Fox => 9.0,
+            Self::Hawk => 30.0,
+        }
+    }
+
+    /// The shadow's half size, when the NPC is far away.
+    fn shadow_size(self) -> Vec2 {
+        match self {
+            Self::Fox => Vec2::new(70.0, 14.0),
+            Self::Hawk => Vec2::new(50.0, 12.0),
+        }
+    }
+}
+
+/// An NPC as the server describes it, e.g. in the `YouJoined` snapshot.
+#[derive(Debug, Clone, Deserialize)]
+pub struct NpcData {
+    pub npc_id: String,
+    pub kind: NpcKind,
+    pub x_position: f32,
+    pub y_position: f32,
+}
+
+#[derive(Resource)]
+struct NpcAssets {
+    circle: Handle<Mesh>,
+    triangle: Handle<Mesh>,
+    rectangle: Handle<Mesh>,
+    fox_fur: Handle<ColorMaterial>,
+    white: Handle<ColorMaterial>,
+    black: Handle<ColorMaterial>,
+    hawk_feathers: Handle<ColorMaterial>,
+    hawk_wing: Handle<ColorMaterial>,
+    beak: Handle<ColorMaterial>,
+}
+
+impl FromWorld for NpcAssets {
+    fn from_world(world: &mut World) -> Self {
+        let mut meshes = world.resource_mut::<Assets<Mesh>>();
+        let circle = meshes.add(Circle::new(1.0));
+        let triangle = meshes.add(Triangle2d::new(
+            Vec2::Y,
+            Vec2::new(-1.0, -1.0),
+            Vec2::new(1.0, -1.0),
+        ));
+        let rectangle = meshes.add(Rectangle::new(1.0, 1.0));
+
+        let mut materials = world.resource_mut::<Assets<ColorMaterial>>();
+        Self {
+            circle,
+            triangle,
+            rectangle,
+            fox_fur: materials.add(Color::srgb(0.9, 0.45, 0.1)),
+            white: materials.add(Color::srgb(0.95, 0.95, 0.9)),
+            black: materials.add(Color::srgb(0.05, 0.05, 0.05)),
+            hawk_feathers: materials.add(Color::srgb(0.45, 0.3, 0.18)),
+            hawk_wing: materials.add(Color::srgb(0.35, 0.22, 0.12)),
+            beak: materials.add(Color::srgb(0.95, 0.75, 0.2)),
+        }
+    }
+}
+
+/// The shapes making up an NPC, flipped to face where it's going.
+#[derive(Component)]
+struct NpcBody;
+
+#[derive(Component)]
+struct HawkWing;
+
+#[derive(Component)]
+struct NpcShadow(Handle<ColorMaterial>);
+
+/// The "!" above your duck while an NPC is close.
+#[derive(Component)]
+struct ThreatWarning;
+
+fn listen_for_spawn_npc_bevy_event(
+    mut commands: Commands,
+    mut event_reader: EventReader<SpawnNpcBevyEvent>,
+    mut npc_query: Query<(&Npc, &mut NetworkPosition)>,
+    npc_assets: Res<NpcAssets>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    for e in event_reader.read() {
+        info!("Spawning {:?} {}", e.data.kind, e.data.npc_id);
+
+        let position = Vec2::new(e.data.x_position, e.data.y_position);
+        // The server may resend an NPC we already have, e.g. after reconnecting.
+        if let Some((_, mut network_position)) = npc_query
+            .iter_mut()
+            .find(|(npc, _)| npc.id == e.data.npc_id)
+        {
+            network_position.0 = position;
+            continue;
+        }
+        spawn_npc(&mut commands, &npc_assets, &mut materials, &e.data);
+    }
+}
+
+fn listen_for_move_npc_bevy_event(
+    mut event_reader: EventReader<MoveNpcBevyEvent>,
+    mut npc_query: Query<(&Npc, &mut NetworkPosition)>,
+) {
+    for e in event_reader.read() {
+        match npc_query.iter_mut().find(|(npc, _)| npc.id == e.npc_id) {
+            Some((_, mut network_position)) => {
+                network_position.0 = Vec2::new(e.x_position, e.y_position);
+            }
+            None => warn!("Got a move for unknown NPC {}", e.npc_id),
+        }
+    }
+}
+
+fn listen_for_despawn_npc_bevy_event(
+    mut commands: Commands,
+    mut event_reader: EventReader<DespawnNpcBevyEvent>,
+    npc_query: Query<(Entity, &Npc)>,
+) {
+    for e in event_reader.read() {
+        info!("Despawning NPC {}", e.npc_id);
+
+        for (entity, npc) in &npc_query {
+            if npc.id == e.npc_id {
+                commands.entity(entity).despawn_recursive();
+            }
+        }
+    }
+}
+
+fn spawn_npc(
+    commands: &mut Commands,
+    npc_assets: &NpcAssets,
+    materials: &mut Assets<ColorMaterial>,
+    data: &NpcData,
+) {
+    let position = Vec2::new(data.x_position, data.y_position);
+    let shadow_material = materials.add(Color::srgba(0.0, 0.0, 0.0, 0.25));
+
+    commands
+        .spawn((
+            Name::new(format!("{} {}", data.kind.label(), data.npc_id)),
+            Npc {
+                id: data.npc_id.clone(),
+                kind: data.kind,
+            },
+            SpatialBundle::from_transform(Transform::from_translation(
+                position.extend(data.kind.z()),
+            )),
+            NetworkPosition(position),
+            MinimapIcon::THREAT,
+            StateScoped(Screen::Gameplay),
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                NpcShadow(shadow_material.clone()),
+                shape(
+                    &npc_assets.circle,
+                    &shadow_material,
+                    Vec2::new(0.0, -30.0),
+                    SHADOW_Z - data.kind.z(),
+                    data.kind.shadow_size(),
+                ),
+            ));
+            parent
+                .spawn((NpcBody, SpatialBundle::default()))
+                .with_children(|body| match data.kind {
+                    NpcKind::Fox => spawn_fox_shapes(body, npc_assets),
+                    NpcKind::Hawk => spawn_hawk_shapes(body, npc_assets),
+                });
+        });
+}
+
+/// One mesh of an NPC. `size` is the half size, like [`Circle::radius`].
+fn shape(
+    mesh: &Handle<Mesh>,
+    material: &Handle<ColorMaterial>,
+    offset: Vec2,
+    z: f32,
+    size: Vec2,
+) -> MaterialMesh2dBundle<ColorMaterial> {
+    MaterialMesh2dBundle {
+        mesh: Mesh2dHandle(mesh.clone()),
+        material: material.clone(),
+        transform: Transform {
+            translation: offset.extend(z),
+            scale: size.extend(1.0),
+            ..default()
+        },
+        ..default()
+    }
+}
+
+/// Facing right, like the duck sprite.
+fn spawn_fox_shapes(body: &mut ChildBuilder, assets: &NpcAssets) {
+    let fur = &assets.fox_fur;
+    // Tail, with a white tip.
+    body.spawn(
+        shape(
+            &assets.circle,
+            fur,
+            Vec2::new(-70.0, 10.0),
+            0.0,
+            Vec2::new(34.0, 13.0),
+        )
+        .with_rotation(0.3),
+    );
+    body.spawn(shape(
+        &assets.circle,
+        &assets.white,
+        Vec2::new(-100.0, 20.0),
+        0.1,
+        Vec2::splat(10.0),
+    ));
+    // Legs.
+    for x in [-30.0, 30.0] {
+        body.spawn(shape(
+            &assets.rectangle,
+            &assets.black,
+            Vec2::new(x, -28.0),
+            0.1,
+            Vec2::new(8.0, 22.0),
+        ));
+    }
+    body.spawn(shape(
+        &assets.circle,
+        fur,
+        Vec2::ZERO,
+        0.2,
+        Vec2::new(55.0, 26.0),
+    ));
+    // Head, ears, snout and eye.
+    body.spawn(shape(
+        &assets.circle,
+        fur,
+        Vec2::new(55.0, 22.0),
+        0.3,
+        Vec2::splat(22.0),
+    ));
+    for x in [45.0, 63.0] {
+        body.spawn(shape(
+            &assets.triangle,
+            fur,
+            Vec2::new(x, 48.0),
+            0.3,
+            Vec2::new(8.0, 12.0),
+        ));
+    }
+    body.spawn(
+        shape(
+            &assets.triangle,
+            &assets.white,
+            Vec2::new(80.0, 14.0),
+            0.4,
+            Vec2::new(8.0, 14.0),
+        )
+        .with_rotation(-std::f32::consts::FRAC_PI_2),
+    );
+    body.spawn(shape(
+        &assets.circle,
+        &assets.black,
+        Vec2::new(64.0, 28.0),
+        0.5,
+        Vec2::splat(4.0),
+    ));
+}
+
+/// Facing right, like the duck sprite.
+fn spawn_hawk_shapes(body: &mut ChildBuilder, assets: &NpcAssets) {
+    let feathers = &assets.hawk_feathers;
+    // Tail.
+    body.spawn(
+        shape(
+            &assets.triangle,
+            feathers,
+            Vec2::new(-50.0, 0.0),
+            0.0,
+            Vec2::new(12.0, 20.0),
+        )
+        .with_rotation(std::f32::consts::FRAC_PI_2),
+    );
+    body.spawn(shape(
+        &assets.circle,
+        feathers,
+        Vec2::ZERO,
+        0.1,
+        Vec2::new(42.0, 17.0),
+    ));
+    // Head, beak and eye.
+    body.spawn(shape(
+        &assets.circle,
+        feathers,
+        Vec2::new(40.0, 8.0),
+        0.2,
+        Vec2::splat(15.0),
+    ));
+    body.spawn(
+        shape(
+            &assets.triangle,
+            &assets.beak,
+            Vec2::new(58.0, 4.0),
+            0.2,
+            Vec2::new(5.0, 9.0),
+        )
+        .with_rotation(-std::f32::consts::FRAC_PI_2),
+    );
+    body.spawn(shape(
+        &assets.circle,
+        &assets.black,
+        Vec2::new(45.0, 12.0),
+        0.3,
+        Vec2::splat(3.0),
+    ));
+    // Wings, flapped by `animate_hawk_wings`.
+    body.spawn((
+        HawkWing,
+        shape(
+            &assets.triangle,
+            &assets.hawk_wing,
+            Vec2::new(0.0, 30.0),
+            0.4,
+            Vec2::new(30.0, 30.0),
+        ),
+    ));
+}
+
+trait WithRotation {
+    fn with_rotation(self, angle: f32) -> Self;
+}
+
+impl WithRotation for MaterialMesh2dBundle<ColorMaterial> {
+    fn with_rotation(mut self, angle: f32) -> Self {
+        self.transform.rotation = Quat::from_rotation_z(angle);
+        self
+    }
+}
+
+/// Flip NPCs to face the way they're going.
+fn face_movement(
+    npc_query: Query<(&Transform, &NetworkPosition, &Children), With<Npc>>,
+    mut body_query: Query<&mut Transform, (With<NpcBody>, Without<Npc>)>,
+) {
+    for (transform, network_position, children) in &npc_query {
+        let dx = network_position.0.x - transform.translation.x;
+        if dx.abs() < 1.0 {
+            continue;
+        }
+        for child in children {
+            if let Ok(mut body_transform) = body_query.get_mut(*child) {
+                body_transform.scale.x = dx.signum();
+            }
+        }
+    }
+}
+
+fn animate_hawk_wings(time: Res<Time>, mut wing_query: Query<&mut Transform, With<HawkWing>>) {
+    let flap = (time.elapsed_seconds() * 10.0).sin();
+    for mut transform in &mut wing_query {
+        transform.scale.y = 30.0 * flap;
+        transform.translation.y = 30.0 * flap;
+    }
+}
+
+/// How close the NPC is to your duck, from 0 at [`NPC_WARNING_RADIUS`] to 1
+/// right on top of it.
+fn closeness(npc_position: Vec2, player_position: Option<Vec2>) -> f32 {
+    player_position.map_or(0.0, |player_position| {
+        (1.0 - npc_position.distance(player_position) / NPC_WARNING_RADIUS).clamp(0.0, 1.0)
+    })
+}
+
+/// Darken and grow the shadow as the NPC closes in, and swoop hawks down.
+fn show_approach(
+    player_query: Query<&GlobalTransform, With<Player>>,
+    npc_query: Query<(&Npc, &GlobalTransform, &Children)>,
+    mut body_query: Query<&mut Transform, With<NpcBody>>,
+    mut shadow_query: Query<(&NpcShadow, &mut Transform), Without<NpcBody>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    let player_position = player_query
+        .get_single()
+        .ok()
+        .map(|transform| transform.translation().truncate());
+
+    for (npc, npc_transform, children) in &npc_query {
+        let closeness = closeness(npc_transform.translation().truncate(), player_position);
+        // The hawk's body rises above its position, which is where its shadow
+        // falls, so that the shadow shows where it's going to land.
+        let altitude = match npc.kind {
+            NpcKind::Fox => 0.0,
+            NpcKind::Hawk => HAWK_ALTITUDE.lerp(HAWK_SWOOP_ALTITUDE, closeness),
+        };
+
+        for child in children {
+            if let Ok(mut body_transform) = body_query.get_mut(*child) {
+                body_transform.translation.y = altitude;
+            }
+            if let Ok((shadow, mut shadow_transform)) = shadow_query.get_mut(*child) {
+                let size = npc.kind.shadow_size() * (1.0 + 0.5 * closeness);
+                shadow_transform.scale = size.extend(1.0);
+                shadow_transform.translation.y = match npc.kind {
+                    NpcKind::Fox => -30.0,
+                    NpcKind::Hawk => 0.0,
+                };
+                if let Some(material) = materials.get_mut(&shadow.0) {
+                    material.color = Color::srgba(0.0, 0.0, 0.0, 0.25 + 0.4 * closeness);
+                }
+            }
+        }
+    }
+}
+
+/// Show a pulsing "!" above your duck while any NPC is close. It pulses faster
+/// the closer the nearest one is.
+fn update_threat_warning(
+    mut commands: Commands,
+    time: Res<Time>,
+    asset_server: Res<AssetServer>,
+    player_query: Query<(Entity, &GlobalTransform), With<Player>>,
+    npc_query: Query<&GlobalTransform, With<Npc>>,
+    mut warning_query: Query<(Entity, &mut Transform), With<ThreatWarning>>,
+) {
+    let Ok((player, player_transform)) = player_query.get_single() else {
+        return;
+    };
+    let player_position = player_transform.translation().truncate();
+    let closeness = npc_query
+        .iter()
+        .map(|npc_transform| {
+            closeness(
+                npc_transform.translation().truncate(),
+                Some(player_position),
+            )
+        })
+        .fold(0.0, f32::max);
+
+    if closeness <= 0.0 {
+        for (warning, _) in &warning_query {
+            commands.entity(warning).despawn_recursive();
+        }
+        return;
+    }
+
+    if warning_query.is_empty() {
+        commands.entity(player).with_children(|parent| {
+            parent.spawn((
+                Name::new("Threat warning"),
+                ThreatWarning,
+                Text2dBundle {
+                    text: Text::from_section(
+                        "!",
+                        TextStyle {
+                            font: asset_server.load("FiraSans-Bold.ttf"),
+                            font_size: 40.0,
+                            color: WARNING_COLOR,
+                        },
+                    ),
+                    transform: Transform {
+                        translation: WARNING_OFFSET.extend(3.0),
+                        scale: Vec3::new(0.25, 0.25, 1.0),
+                        ..default()
+                    },
+                    ..default()
+                },
+            ));
+        });
+        return;
+    }
+
+    let pulse_speed = 6.0 + 14.0 * closeness;
+    let pulse = 1.0 + 0.25 * (time.elapsed_seconds() * pulse_speed).sin().abs();
+    for (_, mut transform) in &mut warning_query {
+        transform.scale = Vec3::new(0.25 * pulse, 0.25 * pulse, 1.0);
+    }
+}
@@ -1,16 +1,16 @@
 // "Other" player refers to all players that are not the one being controlled by the user
 
 use bevy::{
-    audio::{AudioPlugin, SpatialScale},
-    color::palettes::css::*,
-    prelude::*,
     prelude::*,
     render::texture::{ImageLoaderSettings, ImageSampler},
-    sprite::MaterialMesh2dBundle,
 };
 use serde::Deserialize;
 
 use super::{
+    cracker::CrackerData,
+    interpolation::NetworkPosition,
+    npc::NpcData,
+    player::QuackAudio,
     player_animation::PlayerAnimationState,
     websocket_connect::{
         OtherPlayerJoinedWsReceived, OtherPlayerMovedWsReceived, OtherPlayerQuackedWsReceived,
@@ -20,7 +20,13 @@ use super::{
 
 use crate::{
     asset_tracking::LoadResource,
-    demo::other_player_animation::{OtherPlayerAnimation, OtherPlayerAnimationState},
+    audio::{AudioChannel, PlaySfx, SfxPriority},
+    demo::{
+        duck_color::unpack_duck_color,
+        minimap::MinimapIcon,
+        quack::DuckQuacked,
+        other_player_animation::{OtherPlayerAnimation, OtherPlayerAnimationState},
+    },
     screens::Screen,
 };
 
@@ -37,8 +43,6 @@ use crate::{
 //     }
 // }
 
-use bevy_kira_audio::*;
-
 // #[derive(Debug, Deserialize)]
 // pub struct NewJoinerData {
 //     pub player_uuid: String,
@@ -110,9 +114,10 @@ pub struct NewJoinerDataWithAllPlayers {
     pub color: String,
     pub x_position: f32,
     pub y_position: f32,
-    pub cracker_x: f32,
-    pub cracker_y: f32,
-    pub cracker_points: u64,
+    #[serde(default)]
+    pub crackers: Vec<CrackerData>,
+    #[serde(default)]
+    pub npcs: Vec<NpcData>,
 
     pub player_points: u64,
 
@@ -150,14 +155,6 @@ pub struct QuackResponseData {
     pub quack_pitch: f32,
 }
 
-// Tag for the listener (e.g., player or camera)
-#[derive(Component)]
-struct Listener;
-
-// Tag for sound emitters
-// #[derive(Component)]
-// struct SoundEmitter;
-
 #[derive(Debug, Deserialize)]
 pub struct OtherMovedReceivedWsMsg {
     pub action_type: S2CActionTypes,
@@ -204,133 +201,16 @@ impl FromWorld for OtherPlayerAssets {
     }
 }
 
-// #[derive(Resource)]
-// pub struct SpatialAudio {
-//     /// The volume will change from `1` at distance `0` to `0` at distance `max_distance`
-//     pub max_distance: f32,
-// }
-
-// impl SpatialAudio {
-//     pub(crate) fn update(
-//         &self,
-//         receiver_transform: &GlobalTransform,
-//         emitters: &Query<(&GlobalTransform, &AudioEmitter)>,
-//         audio_instances: &mut Assets<AudioInstance>,
-//     ) {
-//         for (emitter_transform, emitter) in emitters {
-//             let sound_path = emitter_transform.translation() - receiver_transform.translation();
-//             let volume = (1. - sound_path.length() / self.max_distance)
-//                 .clamp(0., 1.)
-//                 .powi(2);
-
-//             let right_ear_angle = receiver_transform.right().angle_between(sound_path);
-//             let panning = (right_ear_angle.cos() + 1.) / 2.;
-
-//             for instance in emitter.instances.iter() {
-//                 if let Some(instance) = audio_instances.get_mut(instance) {
-//                     instance.set_volume(volume as f64, AudioTween::default());
-//                     instance.set_panning(panning as f64, AudioTween::default());
-//                 }
-//             }
-//         }
-//     }
-// }
-
-// TODO - Still need to figure out proper spatial audio...
-// pub(crate) fn run_spatial_audio(
-//     spatial_audio: Res<SpatialAudio>,
-//     receiver: Query<&GlobalTransform, With<AudioReceiver>>,
-//     emitters: Query<(&GlobalTransform, &AudioEmitter)>,
-//     mut audio_instances: ResMut<Assets<AudioInstance>>,
-// ) {
-//     if let Ok(receiver_transform) = receiver.get_single() {
-//         spatial_audio.update(receiver_transform, &emitters, &mut audio_instances);
-//     }
-// }
-
-// pub(crate) fn cleanup_stopped_spatial_instances(
-//     mut emitters: Query<&mut AudioEmitter>,
-//     instances: ResMut<Assets<AudioInstance>>,
-// ) {
-//     for mut emitter in emitters.iter_mut() {
-//         let handles = &mut emitter.instances;
-
-//         handles.retain(|handle| {
-//             if let Some(_instance) = instances.get(handle) {
-//                 _instance.handle.state() != PlaybackState::Stopped
-//             } else {
-//                 true
-//             }
-//         });
-//     }
-// }
-
-/// Component for audio emitters
-///
-/// Add [`Handle<AudioInstance>`]s to control their pan and volume based on emitter
-/// and receiver positions.
-// #[derive(Component, Default)]
-// pub struct AudioEmitter {
-//     /// Audio instances that are played by this emitter
-
-//     /// The same instance should only be on one emitter.
-//     pub instances: Vec<Handle<AudioInstance>>,
-// }
-
-/// Component for the audio receiver
-///
-/// Most likely you will want to add this component to your player or you camera.
-/// The entity needs a [`Transform`] and [`GlobalTransform`]. The view direction of the [`GlobalTransform`]
-/// will
-// #[derive(Component)]
-// pub struct AudioReceiver;
-
-// #[derive(Bundle)]
-// struct SpatialAudioBundle {
-//     audio_source: Handle<bevy_kira_audio::AudioSource>,
-//     transform: Transform,
-//     global_transform: GlobalTransform,
-// }
-
-/// Spatial audio uses the distance to attenuate the sound volume. In 2D with the default camera,
-/// 1 pixel is 1 unit of distance, so we use a scale so that 100 pixels is 1 unit of distance for
-/// audio.
-
-const AUDIO_SCALE: f32 = 1. / 100.0;
-
-#[derive(Component, Default)]
-pub struct Emitter {
-    stopped: bool,
-}
-
 pub(super) fn plugin(app: &mut App) {
     app.register_type::<OtherPlayer>();
     app.load_resource::<OtherPlayerAssets>();
-    app.add_plugins(bevy_kira_audio::AudioPlugin);
-    // app.add_plugins(DefaultPlugins.set(AudioPlugin {
-    //     default_spatial_scale: SpatialScale::new_2d(AUDIO_SCALE),
-    //     ..default()
-    // }));
-    // app.insert_resource(SpatialAudio { max_distance: 25. });
-    // app.init_asset::<AudioSource>();
-    // app.insert_resource(SpatialScale { scale: 1.0 }); // Scale of spatial audio
 
     app.add_systems(Update, other_player_joined_ws_msg_handler);
     app.add_systems(Update, other_player_moved_ws_msg_handler);
     app.add_systems(Update, other_player_quacked_handler);
     app.add_systems(Update, other_player_disconnected_handler);
-
-    // app.add_systems(
-    //     Update,
-    //     run_spatial_audio.run_if(resource_exists::<SpatialAudio>),
-    // );
 }
 
-// app.add_plugins(DefaultPlugins.set(AudioPlugin {
-//     default_spatial_scale: SpatialScale::new_2d(AUDIO_SCALE),
-//     ..default()
-// }));
-
 // spawn player
 pub fn other_player_joined_ws_msg_handler(
     mut event_reader: EventReader<OtherPlayerJoinedWsReceived>,
@@ -400,6 +280,8 @@ pub fn other_player_joined_ws_msg_handler(
                     index: player_animation.get_atlas_index(),
                 },
                 player_animation,
+                NetworkPosition(Vec2::new(e.data.x_position, e.data.y_position)),
+                MinimapIcon::other_duck(unpack_duck_color(e.data.color.clone())),
                 StateScoped(Screen::Gameplay),
             );
 
@@ -441,7 +323,7 @@ pub fn other_player_moved_ws_msg_handler(
         &OtherPlayer,
         &mut Sprite,
         &Name,
-        &mut Transform,
+        &mut NetworkPosition,
         &mut OtherPlayerAnimation,
     )>, // friendly_name: String,
         // color: Color,
@@ -469,7 +351,7 @@ pub fn other_player_moved_ws_msg_handler(
             e
         );
 
-        for (_other_player, mut sprite, name, mut transform, mut animation) in
+        for (_other_player, mut sprite, name, mut network_position, mut animation) in
             other_players.iter_mut()
         {
             info!("checking vs id map: {}", name.to_string());
@@ -479,8 +361,10 @@ pub fn other_player_moved_ws_msg_handler(
                     other_player_moved_response_data.player_uuid
                 );
 
-                transform.translation.x = other_player_moved_response_data.new_x_position;
-                transform.translation.y = other_player_moved_response_data.new_y_position;
+                network_position.0 = Vec2::new(
+                    other_player_moved_response_data.new_x_position,
+                    other_player_moved_response_data.new_y_position,
+                );
 
                 let dx = other_player_moved_response_data.new_x_position
                     - other_player_moved_response_data.old_x_position;
@@ -495,24 +379,6 @@ pub fn other_player_moved_ws_msg_handler(
     }
 }
 
-pub fn unpack_duck_color(color: String) -> Color {
-    match color.as_str() {
-        // "blue" => Color::srgba(0.5, 0.5, 1.0, 1.),
-        // "red" => Color::srgba(0.5, 0.1, 0., 1.),
-        // "green" => Color::srgba(0., 0.9, 0., 1.),
-        // "white" => Color::WHITE,
-        // _ => Color::srgba(0.8, 1.0, 1.0, 1.0), // Teal
-        // _ => Color::srgba(1.0, 1.0, 0.8, 1.0), // Yellow
-        // _ => Color::srgba(0.70, 0.6, 1.0, 1.0), // Purple
-        // _ => Color::srgba(1.0, 0.8, 0.1, 1.0), // Pink
-        // _ => Color::srgba(1.0, 0.78, 0.49, 1.), // Light orange
-        // _ => Color::srgba(0.54, 0.81, 0.94, 1.), // Baby blue
-        // _ => Color::srgba(0.60, 1.0, 0.60, 1.), // Lime Green
-        // _ => Color::srgba(0.1, 0.5, 0.1, 1.), // Forest
-        _ => Color::WHITE, // Default color
-    }
-}
-
 fn other_player_disconnected_handler(
     mut commands: Commands,
     mut event_reader: EventReader<UserDisconnectedBevyEvent>,
@@ -539,14 +405,13 @@ fn other_player_disconnected_handler(
     }
 }
 
-// Just plays regular quack sound for now, without spatial audio
+/// Plays the quack from where the other duck is.
 fn other_player_quacked_handler(
-    mut commands: Commands,
+    mut play_sfx: EventWriter<PlaySfx>,
     mut event_reader: EventReader<OtherPlayerQuackedWsReceived>,
-    asset_server: Res<AssetServer>,
-    audio: Res<Audio>,
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut materials: ResMut<Assets<ColorMaterial>>,
+    quack_audio: Res<QuackAudio>,
+    other_players: Query<(Entity, &Name), With<OtherPlayer>>,
+    mut duck_quacked_event_writer: EventWriter<DuckQuacked>,
 ) {
     for e in event_reader.read() {
         let other_player_quacked_response_data = serde_json::from_value(e.data.clone())
@@ -566,90 +431,27 @@ fn other_player_quacked_handler(
             other_player_quacked_response_data
         );
 
-        // Get the position from the event data
-        let _quack_position = Vec3::new(
+        let mut quacker = None;
+        for (entity, name) in other_players.iter() {
+            if name.to_string() == other_player_quacked_response_data.player_uuid {
+                duck_quacked_event_writer.send(DuckQuacked { duck: entity });
+                quacker = Some(entity);
+            }
+        }
+
+        let position = Vec2::new(
             other_player_quacked_response_data.player_x_position,
             other_player_quacked_response_data.player_y_position,
-            0.0, // Assuming 2D game, z-coordinate is 0
         );
-
-        // let audio_handle = asset_server.load("audio/sound_effects/duck-quack.ogg");
-
-        // if let Some(audio_handle) = audio_assets.get(&quack_audio.sound_handle) {
-        // Spawn an audio source to play the sound
-        // commands.spawn()
-
-        // Nonspacial version
-        // commands.spawn(AudioSourceBundle {
-        //     source: quack_audio.sound_handle.clone(), // Clone the handle to use it
-
-        //     ..Default::default()                // Use default values for other fields
-        // });
-
-        //  Non spatial
-        // commands
-        //     .spawn(TransformBundle::from_transform(Transform::from_xyz(
-        //         other_player_quacked_response_data.player_x_position,
-        //         other_player_quacked_response_data.player_y_position,
-        //         0.0,
-        //     ))) // Position emitter to the right
-        //     .insert(SoundEmitter);
-
-        // let audio_handle = asset_server.load("audio/sound_effects/duck-quack.ogg");
-        // audio.play(audio_handle);
-
-        commands.spawn((
-            MaterialMesh2dBundle {
-                mesh: meshes.add(Circle::new(15.0)).into(),
-                material: materials.add(Color::from(BLUE)),
-                transform: Transform::from_translation(Vec3::new(
-                    other_player_quacked_response_data.player_x_position,
-                    other_player_quacked_response_data.player_y_position,
-                    100.0,
-                )),
-                ..default()
-            },
-            Emitter::default(),
-            AudioBundle {
-                source: asset_server.load("audio/sound_effects/duck-quack.ogg"),
-                settings: PlaybackSettings::ONCE.with_spatial(true),
-            },
-        ));
-
-        // commands.spawn(SpatialAudioBundle {
-        //     audio_source: audio_handle.clone(),
-        //     transform: Transform::from_translation(Vec3::new(
-        //         other_player_quacked_response_data.player_x_position,
-        //         other_player_quacked_response_data.player_y_position,
-        //         0.0,
-        //     )),
-        //     global_transform: GlobalTransform::default(),
-        // });
-        // // Play the audio at the position defined above
-        // audio.play(audio_handle.clone());
-
-        //     audio_handle,
-        //     Transform {
-        //         translation: Vec3::new(other_player_quacked_response_data.player_x_position,
-        //             other_player_quacked_response_data.player_y_position, 0.0), // Position the audio in 3D space
-        //         ..Default::default()
-        //     },
-        //     GlobalTransform::default(), // Required for spatial positioning
-        // ));
-
-        // commands.spawn((
-        //     SpatialAudio {
-        //         emitter_position: quack_position, // Position of the quacking player
-        //         listener_position: listener_transform.translation, // Player's current position (listener)
-        //         ..Default::default() // Other default values, such as attenuation and falloff
-        //     },
-        //     audio.sound_handle.clone(), // Play the quack sound
-        //     PlaybackSettings::ONCE.with_pitch(other_player_quacked_response_data.quack_pitch), // Adjust the pitch dynamically
-        // ));
-
-        //     println!("Received quack message! Playing sound.");
-        // } else {
-        //     println!("Audio not loaded yet.");
-        // }
+        let mut quack = PlaySfx::new(AudioChannel::RemotePlayers, quack_audio.sound_handle.clone())
+            .with_priority(SfxPriority::High)
+            .at(position);
+        if let Some(quacker) = quacker {
+            quack = quack.following(quacker);
+        }
+        if other_player_quacked_response_data.quack_pitch > 0. {
+            quack = quack.with_speed(other_player_quacked_response_data.quack_pitch);
+        }
+        play_sfx.send(quack);
     }
 }
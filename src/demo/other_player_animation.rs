@@ -4,19 +4,18 @@
 //! - [Sprite animation](https://github.com/bevyengine/bevy/blob/latest/examples/2d/sprite_animation.rs)
 //! - [Timers](https://github.com/bevyengine/bevy/blob/latest/examples/time/timers.rs)
 
-use bevy::{color::palettes::css::BLUE, prelude::*, render::texture::{ImageLoaderSettings, ImageSampler}, sprite::MaterialMesh2dBundle
-};
+use bevy::prelude::*;
 use rand::prelude::*;
 use std::time::Duration;
 
 use crate::{
-    audio::SoundEffect,
+    audio::{AudioChannel, PlaySfx, SfxPriority},
     demo::{movement::MovementController, player::PlayerAssets},
     AppSet,
 };
 
 use super::{
-    other_player::{Emitter, OtherPlayerAssets},
+    other_player::OtherPlayerAssets,
     player::QuackAudio,
     websocket_connect::{OtherPlayerMovedWsReceived, OtherPlayerQuackedWsReceived},
 };
@@ -60,49 +59,25 @@ fn update_animation_atlas(mut query: Query<(&OtherPlayerAnimation, &mut TextureA
 }
 
 /// If the player is moving, play a step sound effect synchronized with the
-/// animation.
+/// animation, from where their duck is.
 fn trigger_step_sound_effect(
-    mut commands: Commands,
+    mut play_sfx: EventWriter<PlaySfx>,
     player_assets: Res<OtherPlayerAssets>,
-    mut step_query: Query<(&OtherPlayerAnimation, &Transform)>,
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut materials: ResMut<Assets<ColorMaterial>>,
+    step_query: Query<(Entity, &OtherPlayerAnimation, &Transform)>,
 ) {
-    for (animation, transform) in &mut step_query {
+    for (entity, animation, transform) in &step_query {
         if animation.state == OtherPlayerAnimationState::Walking
             && animation.changed()
             && (animation.frame == 2 || animation.frame == 5)
         {
             let rng = &mut rand::thread_rng();
             let random_step = player_assets.steps.choose(rng).unwrap();
-
-            // TODO - this is not spatial sound currently
-            // commands.spawn((
-            //     AudioBundle {
-            //         source: random_step.clone(),
-            //         settings: PlaybackSettings::DESPAWN,
-            //     },
-            //     SoundEffect,
-            // ));
-
-            // Sound
-            commands.spawn((
-                MaterialMesh2dBundle {
-                    mesh: meshes.add(Circle::new(15.0)).into(),
-                    material: materials.add(Color::from(BLUE)),
-                    transform: Transform::from_translation(Vec3::new(
-                        transform.translation.x,
-                        transform.translation.y,
-                        100.0,
-                    )),
-                    ..default()
-                },
-                Emitter::default(),
-                AudioBundle {
-                    source: random_step.clone(),
-                    settings: PlaybackSettings::ONCE.with_spatial(true),
-                },
-            ));
+            play_sfx.send(
+                PlaySfx::new(AudioChannel::RemotePlayers, random_step.clone())
+                    .with_priority(SfxPriority::Low)
+                    .at(transform.translation.truncate())
+                    .following(entity),
+            );
         }
     }
 }
@@ -6,17 +6,41 @@ use virtual_joystick::{
     VirtualJoystickPlugin,
 };
 
-use crate::demo::other_player::{unpack_duck_color, NewJoinerDataWithAllPlayers};
+use crate::demo::duck_color::unpack_duck_color;
+use crate::input::input_map::{ActionInput, InputAction};
+use crate::demo::other_player::NewJoinerDataWithAllPlayers;
 use crate::{
     asset_tracking::LoadResource,
-    demo::{movement::MovementController, player_animation::PlayerAnimation},
+    audio::{AudioChannel, PlaySfx, SfxPriority, EAR_GAP},
+    demo::{
+        camera::Pinching,
+        collision::Collider,
+        cracker::{PickupRadius, BASE_PICKUP_RADIUS},
+        dash::Stamina,
+        death::player_alive,
+        emote::emote_wheel_open,
+        minimap::MinimapIcon,
+        movement::MovementController,
+        player_animation::PlayerAnimation,
+        quack::DuckQuacked,
+    },
     screens::Screen,
 };
 
 use super::websocket_connect::{
-    MoveCrackersBevyEvent, OtherPlayerJoinedWsReceived, YouJoinedWsReceived,
+    OtherPlayerJoinedWsReceived, SpawnCrackerBevyEvent, SpawnNpcBevyEvent, YouJoinedWsReceived,
 };
 
+/// How much bigger your duck is drawn than its sprite.
+const DUCK_SCALE: f32 = 4.0;
+
+/// Roughly the duck's body on the 4x scaled sprite, leaving the tail and beak
+/// free to overlap obstacles a little.
+pub const DUCK_COLLIDER_RADIUS: f32 = 36.0;
+
+/// Your duck's top speed without any buffs.
+pub const DUCK_MAX_SPEED: f32 = 500.0;
+
 #[derive(Resource)]
 pub struct QuackAudio {
     pub sound_handle: Handle<AudioSource>,
@@ -28,12 +52,15 @@ pub(super) fn plugin(app: &mut App) {
 
     app.add_plugins(VirtualJoystickPlugin::<String>::default());
     app.add_systems(Startup, create_joystick_scene);
-    app.add_systems(Update, handle_joystick_or_keyboard_input);
+    app.add_systems(Update, handle_joystick_or_keyboard_input.run_if(player_alive));
     app.add_systems(Startup, quack_sound_setup);
     app.add_systems(Startup, add_quack_button);
-    app.add_systems(Update, spacebar_quack_system);
+    app.add_systems(
+        Update,
+        spacebar_quack_system.run_if(not(emote_wheel_open).and_then(player_alive)),
+    );
     app.add_systems(Update, you_joined_ws_msg_handler);
-    app.add_systems(Update, quack_btn_handler);
+    app.add_systems(Update, quack_btn_handler.run_if(player_alive));
     app.add_systems(Startup, spacial_listener_setup);
 }
 
@@ -46,24 +73,23 @@ fn spacial_listener_setup(
 }
 
 fn quack_btn_handler(
-    mut commands: Commands,
-    interaction_query: Query<(Entity, &Interaction), Changed<Interaction>>,
+    interaction_query: Query<(Entity, &Interaction), (Changed<Interaction>, With<QuackBtnButton>)>,
     audio: Res<QuackAudio>,
-    audio_assets: Res<Assets<AudioSource>>,
+    mut play_sfx: EventWriter<PlaySfx>,
+    player_query: Query<Entity, With<Player>>,
+    mut duck_quacked_event_writer: EventWriter<DuckQuacked>,
 ) {
     for (_entity, interaction) in &interaction_query {
         if matches!(interaction, Interaction::Pressed) {
             println!("clicked quack btn!");
 
-            if let Some(_) = audio_assets.get(&audio.sound_handle) {
-                // Spawn an audio source to play the sound
-                commands.spawn(AudioSourceBundle {
-                    source: audio.sound_handle.clone(), // Clone the handle to use it
-                    ..Default::default()                // Use default values for other fields
-                });
-                println!("Playing YouGotCrackers sound.");
-            } else {
-                println!("Audio not loaded yet.");
+            play_sfx.send(
+                PlaySfx::new(AudioChannel::Sfx, audio.sound_handle.clone())
+                    .with_priority(SfxPriority::High),
+            );
+
+            for duck in &player_query {
+                duck_quacked_event_writer.send(DuckQuacked { duck });
             }
         }
     }
@@ -101,23 +127,22 @@ fn quack_sound_setup(mut commands: Commands, asset_server: Res<AssetServer>) {
 }
 
 fn spacebar_quack_system(
-    mut commands: Commands,
     audio: Res<QuackAudio>,
-    keyboard_input: Res<ButtonInput<KeyCode>>, // Input resource for key events
-    audio_assets: Res<Assets<AudioSource>>,    // Query to find entities to affect
+    input: ActionInput,
+    mut play_sfx: EventWriter<PlaySfx>,
+    player_query: Query<Entity, With<Player>>,
+    mut duck_quacked_event_writer: EventWriter<DuckQuacked>,
 ) {
-    if keyboard_input.just_pressed(KeyCode::Space) {
+    if input.just_pressed(InputAction::Quack) {
         println!("Space pressed!");
 
-        if let Some(_) = audio_assets.get(&audio.sound_handle) {
-            // Spawn an audio source to play the sound
-            commands.spawn(AudioSourceBundle {
-                source: audio.sound_handle.clone(),
-                ..Default::default()
-            });
-            println!("Playing your quack sound.");
-        } else {
-            println!("Audio not loaded yet.");
+        play_sfx.send(
+            PlaySfx::new(AudioChannel::Sfx, audio.sound_handle.clone())
+                .with_priority(SfxPriority::High),
+        );
+
+        for duck in &player_query {
+            duck_quacked_event_writer.send(DuckQuacked { duck });
         }
     }
 }
@@ -153,7 +178,8 @@ pub struct Player;
 // spawn player
 pub fn you_joined_ws_msg_handler(
     mut event_reader: EventReader<YouJoinedWsReceived>,
-    mut bevy_move_crackers_event_writer: EventWriter<MoveCrackersBevyEvent>,
+    mut bevy_spawn_cracker_event_writer: EventWriter<SpawnCrackerBevyEvent>,
+    mut bevy_spawn_npc_event_writer: EventWriter<SpawnNpcBevyEvent>,
     mut commands: Commands,
     player_assets_op: Option<Res<PlayerAssets>>,
     mut texture_atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
@@ -176,22 +202,24 @@ pub fn you_joined_ws_msg_handler(
                         // color: sender_game_data.color.clone(),
                         // x_position: sender_game_data.x_pos,
                         // y_position: sender_game_data.x_pos,
-                        cracker_x: 0.,
-                        cracker_y: 0.,
-                        cracker_points: 0,
+                        crackers: vec![],
+                        npcs: vec![],
                         player_points: 0,
                         all_other_players: vec![],
                     }
                 });
 
+            for npc in you_joined_response_data.npcs.iter() {
+                bevy_spawn_npc_event_writer.send(SpawnNpcBevyEvent { data: npc.clone() });
+            }
+
             // play sound effect
 
-            bevy_move_crackers_event_writer.send(MoveCrackersBevyEvent {
-                x_position: you_joined_response_data.cracker_x,
-                y_position: you_joined_response_data.cracker_y,
-                points: you_joined_response_data.cracker_points,
-                you_got_crackers: false,
-            });
+            for cracker in you_joined_response_data.crackers.iter() {
+                bevy_spawn_cracker_event_writer.send(SpawnCrackerBevyEvent {
+                    data: cracker.clone(),
+                });
+            }
 
             info!("In player.rs handling the You joined event {:?}!", e);
             let layout =
@@ -207,7 +235,7 @@ pub fn you_joined_ws_msg_handler(
                     SpriteBundle {
                         texture: player_assets.ducky.clone(),
                         transform: Transform {
-                            scale: Vec2::splat(4.0).extend(2.0),
+                            scale: Vec2::splat(DUCK_SCALE).extend(2.0),
                             translation: Vec3::new(
                                 you_joined_response_data.x_position,
                                 you_joined_response_data.y_position,
@@ -226,9 +254,15 @@ pub fn you_joined_ws_msg_handler(
                         index: player_animation.get_atlas_index(),
                     },
                     MovementController {
-                        max_speed: 500.,
+                        max_speed: DUCK_MAX_SPEED,
                         ..default()
                     },
+                    PickupRadius(BASE_PICKUP_RADIUS),
+                    Stamina::default(),
+                    Collider::Circle {
+                        radius: DUCK_COLLIDER_RADIUS,
+                    },
+                    MinimapIcon::YOU,
                     player_animation,
                     StateScoped(Screen::Gameplay),
                 ))
@@ -252,7 +286,8 @@ pub fn you_joined_ws_msg_handler(
                     });
                 })
                 .with_children(|parent| {
-                    let gap = 40.0;
+                    // The listener is scaled with the duck, ear offsets included.
+                    let gap = EAR_GAP / DUCK_SCALE;
 
                     println!("Adding spatial listener!");
 
@@ -298,26 +333,37 @@ pub fn you_joined_ws_msg_handler(
 }
 
 fn handle_joystick_or_keyboard_input(
-    input: Res<ButtonInput<KeyCode>>,
+    input: ActionInput,
     mut joystick: EventReader<VirtualJoystickEvent<String>>,
+    pinching: Res<Pinching>,
     mut controller_query: Query<&mut MovementController, With<Player>>,
 ) {
     // Collect directional input.
     let mut intent = Vec2::ZERO;
-    if input.pressed(KeyCode::KeyW) || input.pressed(KeyCode::ArrowUp) {
+    if input.pressed(InputAction::MoveUp) {
         intent.y += 1.0;
     }
-    if input.pressed(KeyCode::KeyS) || input.pressed(KeyCode::ArrowDown) {
+    if input.pressed(InputAction::MoveDown) {
         intent.y -= 1.0;
     }
-    if input.pressed(KeyCode::KeyA) || input.pressed(KeyCode::ArrowLeft) {
+    if input.pressed(InputAction::MoveLeft) {
         intent.x -= 1.0;
     }
-    if input.pressed(KeyCode::KeyD) || input.pressed(KeyCode::ArrowRight) {
+    if input.pressed(InputAction::MoveRight) {
         intent.x += 1.0;
     }
 
+    // Gamepad stick overrides the keys too, as long as it's in use.
+    let stick = input.left_stick();
+    if stick != Vec2::ZERO {
+        intent = stick;
+    }
+
     for j in joystick.read() {
+        // The first finger of a pinch-zoom would otherwise steer the duck.
+        if pinching.0 || input.blocked() {
+            continue;
+        }
         let Vec2 { x, y } = j.axis();
 
         // Joystick overrides the arrow keys! (but don't override if not touching joystick)
@@ -341,6 +387,9 @@ pub struct PlayerAssets {
     pub ducky: Handle<Image>,
     #[dependency]
     pub steps: Vec<Handle<AudioSource>>,
+    /// There's no real splash sound yet, so this is a slowed down boing.
+    #[dependency]
+    pub splash: Handle<AudioSource>,
 }
 
 impl PlayerAssets {
@@ -349,6 +398,7 @@ impl PlayerAssets {
     pub const PATH_STEP_2: &'static str = "audio/sound_effects/step2.ogg";
     pub const PATH_STEP_3: &'static str = "audio/sound_effects/step3.ogg";
     pub const PATH_STEP_4: &'static str = "audio/sound_effects/step4.ogg";
+    pub const PATH_SPLASH: &'static str = "audio/sound_effects/boing.ogg";
 }
 
 impl FromWorld for PlayerAssets {
@@ -367,6 +417,7 @@ impl FromWorld for PlayerAssets {
                 assets.load(PlayerAssets::PATH_STEP_3),
                 assets.load(PlayerAssets::PATH_STEP_4),
             ],
+            splash: assets.load(PlayerAssets::PATH_SPLASH),
         }
     }
 }
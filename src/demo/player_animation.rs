@@ -9,8 +9,11 @@ use rand::prelude::*;
 use std::time::Duration;
 
 use crate::{
-    audio::SoundEffect,
-    demo::{movement::MovementController, player::PlayerAssets},
+    audio::{AudioChannel, PlaySfx, SfxPriority},
+    demo::{
+        movement::{MovementController, SwimmingChanged},
+        player::PlayerAssets,
+    },
     AppSet,
 };
 
@@ -25,6 +28,7 @@ pub(super) fn plugin(app: &mut App) {
                 update_animation_movement,
                 update_animation_atlas,
                 trigger_step_sound_effect,
+                trigger_splash_sound_effect,
             )
                 .chain()
                 .run_if(resource_exists::<PlayerAssets>)
@@ -33,7 +37,16 @@ pub(super) fn plugin(app: &mut App) {
     );
 }
 
-/// Update the sprite direction and animation state (idling/walking).
+/// Paddling reuses the step sounds, slower and quieter.
+const PADDLE_SPEED: f32 = 0.5;
+const PADDLE_VOLUME: f32 = 0.4;
+/// Below this speed the duck is just floating, and doesn't paddle.
+const PADDLE_MIN_SPEED: f32 = 20.0;
+const SPLASH_IN_SPEED: f32 = 0.6;
+const SPLASH_OUT_SPEED: f32 = 0.9;
+const SPLASH_OUT_VOLUME: f32 = 0.5;
+
+/// Update the sprite direction and animation state (idling/walking/swimming).
 fn update_animation_movement(
     mut player_query: Query<(&MovementController, &mut Sprite, &mut PlayerAnimation)>,
 ) {
@@ -43,7 +56,9 @@ fn update_animation_movement(
             sprite.flip_x = dx < 0.0;
         }
 
-        let animation_state = if controller.intent == Vec2::ZERO {
+        let animation_state = if controller.swimming {
+            PlayerAnimationState::Swimming
+        } else if controller.intent == Vec2::ZERO {
             PlayerAnimationState::Idling
         } else {
             PlayerAnimationState::Walking
@@ -69,27 +84,51 @@ fn update_animation_atlas(mut query: Query<(&PlayerAnimation, &mut TextureAtlas)
 }
 
 /// If the player is moving, play a step sound effect synchronized with the
-/// animation.
+/// animation. In the water, play a paddling sound instead.
 fn trigger_step_sound_effect(
-    mut commands: Commands,
+    mut play_sfx: EventWriter<PlaySfx>,
+    player_assets: Res<PlayerAssets>,
+    mut step_query: Query<(&PlayerAnimation, &MovementController)>,
+) {
+    for (animation, controller) in &mut step_query {
+        if !animation.changed() {
+            continue;
+        }
+        let rng = &mut rand::thread_rng();
+        let random_step = player_assets.steps.choose(rng).unwrap();
+        let step =
+            PlaySfx::new(AudioChannel::Sfx, random_step.clone()).with_priority(SfxPriority::Low);
+        play_sfx.send(match animation.state {
+            PlayerAnimationState::Walking if animation.frame == 2 || animation.frame == 5 => step,
+            PlayerAnimationState::Swimming
+                if animation.frame == 0 && controller.velocity.length() > PADDLE_MIN_SPEED =>
+            {
+                step.with_speed(PADDLE_SPEED).with_volume(PADDLE_VOLUME)
+            }
+            _ => continue,
+        });
+    }
+}
+
+/// Splash when the player gets into or out of the water.
+fn trigger_splash_sound_effect(
+    mut play_sfx: EventWriter<PlaySfx>,
     player_assets: Res<PlayerAssets>,
-    mut step_query: Query<&PlayerAnimation>,
+    mut event_reader: EventReader<SwimmingChanged>,
+    player_query: Query<(), With<PlayerAnimation>>,
 ) {
-    for animation in &mut step_query {
-        if animation.state == PlayerAnimationState::Walking
-            && animation.changed()
-            && (animation.frame == 2 || animation.frame == 5)
-        {
-            let rng = &mut rand::thread_rng();
-            let random_step = player_assets.steps.choose(rng).unwrap();
-            commands.spawn((
-                AudioBundle {
-                    source: random_step.clone(),
-                    settings: PlaybackSettings::DESPAWN,
-                },
-                SoundEffect,
-            ));
+    for e in event_reader.read() {
+        if !player_query.contains(e.entity) {
+            continue;
         }
+        let splash = PlaySfx::new(AudioChannel::Sfx, player_assets.splash.clone());
+        play_sfx.send(if e.swimming {
+            splash.with_speed(SPLASH_IN_SPEED)
+        } else {
+            splash
+                .with_speed(SPLASH_OUT_SPEED)
+                .with_volume(SPLASH_OUT_VOLUME)
+        });
     }
 }
 
@@ -107,6 +146,7 @@ pub struct PlayerAnimation {
 pub enum PlayerAnimationState {
     Idling,
     Walking,
+    Swimming,
 }
 
 impl PlayerAnimation {
@@ -118,6 +158,14 @@ impl PlayerAnimation {
     const WALKING_FRAMES: usize = 6;
     /// The duration of each walking frame.
     const WALKING_INTERVAL: Duration = Duration::from_millis(50);
+    /// Where the swimming frames start. The atlas has no swimming row yet, so
+    /// the duck paddles through its idle frames, slowly. Point this at the new
+    /// row once the art has one.
+    const SWIMMING_START: usize = 0;
+    /// The number of swimming frames.
+    const SWIMMING_FRAMES: usize = 2;
+    /// The duration of each swimming frame.
+    const SWIMMING_INTERVAL: Duration = Duration::from_millis(300);
 
     fn idling() -> Self {
         Self {
@@ -135,6 +183,14 @@ impl PlayerAnimation {
         }
     }
 
+    fn swimming() -> Self {
+        Self {
+            timer: Timer::new(Self::SWIMMING_INTERVAL, TimerMode::Repeating),
+            frame: 0,
+            state: PlayerAnimationState::Swimming,
+        }
+    }
+
     pub fn new() -> Self {
         Self::idling()
     }
@@ -149,6 +205,7 @@ impl PlayerAnimation {
             % match self.state {
                 PlayerAnimationState::Idling => Self::IDLE_FRAMES,
                 PlayerAnimationState::Walking => Self::WALKING_FRAMES,
+                PlayerAnimationState::Swimming => Self::SWIMMING_FRAMES,
             };
     }
 
@@ -158,6 +215,7 @@ impl PlayerAnimation {
             match state {
                 PlayerAnimationState::Idling => *self = Self::idling(),
                 PlayerAnimationState::Walking => *self = Self::walking(),
+                PlayerAnimationState::Swimming => *self = Self::swimming(),
             }
         }
     }
@@ -172,6 +230,7 @@ impl PlayerAnimation {
         match self.state {
             PlayerAnimationState::Idling => self.frame,
             PlayerAnimationState::Walking => 6 + self.frame,
+            PlayerAnimationState::Swimming => Self::SWIMMING_START + self.frame,
         }
     }
 }
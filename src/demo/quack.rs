@@ -0,0 +1,110 @@
+//! The "QUACK!" bubble that pops up above a duck when it quacks.
+//!
+//! Both your own quacks and other players' quacks send a [`DuckQuacked`]
+//! event, which spawns a bubble as a child of the quacking duck. The bubble
+//! rises and fades, and is despawned when its timer runs out.
+
+use bevy::prelude::*;
+
+use crate::{screens::Screen, AppSet};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_event::<DuckQuacked>();
+
+    app.add_systems(
+        Update,
+        (
+            tick_quack_bubbles.in_set(AppSet::TickTimers),
+            (spawn_quack_bubbles, animate_quack_bubbles)
+                .chain()
+                .in_set(AppSet::Update),
+        )
+            .run_if(in_state(Screen::Gameplay)),
+    );
+}
+
+/// A duck quacked. Used for both your duck and other players' ducks.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct DuckQuacked {
+    pub duck: Entity,
+}
+
+#[derive(Component)]
+struct QuackBubble(Timer);
+
+const QUACK_BUBBLE_SECONDS: f32 = 0.8;
+/// In the duck's local space, where the sprite is 32 units tall.
+const QUACK_BUBBLE_OFFSET: Vec2 = Vec2::new(10.0, 22.0);
+const QUACK_BUBBLE_RISE: f32 = 8.0;
+const QUACK_BUBBLE_COLOR: Color = Color::srgb(1.0, 0.95, 0.4);
+
+fn spawn_quack_bubbles(
+    mut commands: Commands,
+    mut events: EventReader<DuckQuacked>,
+    asset_server: Res<AssetServer>,
+    children_query: Query<&Children>,
+    bubble_query: Query<(), With<QuackBubble>>,
+) {
+    for event in events.read() {
+        // Quacking again replaces the old bubble instead of stacking them.
+        for child in children_query.get(event.duck).into_iter().flatten() {
+            if bubble_query.contains(*child) {
+                commands.entity(*child).despawn_recursive();
+            }
+        }
+
+        let Some(mut duck) = commands.get_entity(event.duck) else {
+            continue;
+        };
+        duck.with_children(|parent| {
+            parent.spawn((
+                Name::new("Quack bubble"),
+                QuackBubble(Timer::from_seconds(QUACK_BUBBLE_SECONDS, TimerMode::Once)),
+                Text2dBundle {
+                    text: Text::from_section(
+                        "QUACK!",
+                        TextStyle {
+                            font: asset_server.load("FiraSans-Bold.ttf"),
+                            font_size: 25.0,
+                            color: QUACK_BUBBLE_COLOR,
+                        },
+                    ),
+                    transform: Transform {
+                        translation: QUACK_BUBBLE_OFFSET.extend(2.0),
+                        // Cancel out the duck's 4x scale, like the name label.
+                        scale: Vec3::new(0.25, 0.25, 1.0),
+                        ..default()
+                    },
+                    ..default()
+                },
+            ));
+        });
+    }
+}
+
+fn tick_quack_bubbles(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut bubble_query: Query<(Entity, &mut QuackBubble)>,
+) {
+    for (entity, mut bubble) in &mut bubble_query {
+        bubble.0.tick(time.delta());
+        if bubble.0.finished() {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+/// Pop the bubble in, then let it rise and fade out.
+fn animate_quack_bubbles(mut bubble_query: Query<(&QuackBubble, &mut Transform, &mut Text)>) {
+    for (bubble, mut transform, mut text) in &mut bubble_query {
+        let progress = bubble.0.fraction();
+        let pop = 1.0 + 0.4 * (1.0 - (progress * 5.0).min(1.0));
+
+        transform.translation.y = QUACK_BUBBLE_OFFSET.y + QUACK_BUBBLE_RISE * progress;
+        transform.scale = Vec3::new(0.25 * pop, 0.25 * pop, 1.0);
+        for section in &mut text.sections {
+            section.style.color = QUACK_BUBBLE_COLOR.with_alpha(1.0 - progress * progress);
+        }
+    }
+}
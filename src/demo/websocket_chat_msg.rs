@@ -0,0 +1,62 @@
+use std::io::ErrorKind;
+use bevy::prelude::*;
+use tungstenite::Message;
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_event::<ChatRequestEvent>();
+    app.add_systems(Update, chat_request_bevy_event_listener);
+}
+
+use super::websocket_connect::WebSocketClient;
+
+/// Send a chat message. The server passes it on to the other players.
+#[derive(Event)]
+pub struct ChatRequestEvent {
+    pub message: String,
+}
+
+// Listens for bevy events for ws messages and fires them off to the server
+fn chat_request_bevy_event_listener(
+    mut ev_chat_request: EventReader<ChatRequestEvent>,
+    mut entities_with_client: Query<(&mut WebSocketClient,)>,
+) {
+    for ev in ev_chat_request.read() {
+        debug!("heard chat request bevy event");
+        for mut client in entities_with_client.iter_mut() {
+            debug!("sending chat request ws msg");
+            let message = build_chat_request_msg(ev.message.clone());
+
+            match client.0 .0 .0.send(Message::text(message)) {
+                Ok(_) => info!("Chat request ws msg successfully sent to server!"),
+                Err(tungstenite::Error::Io(e)) if e.kind() == ErrorKind::WouldBlock => { /* ignore */
+                }
+                Err(e) => {
+                    warn!("Could not send the message: {e:?}");
+                }
+            }
+        }
+    }
+}
+
+#[derive(serde::Serialize)]
+struct ChatRequestData {
+    message: String,
+}
+
+#[derive(serde::Serialize)]
+struct ChatRequest {
+    action_type: String,
+    data: ChatRequestData,
+}
+
+fn build_chat_request_msg(message: String) -> String {
+    let chat_request = ChatRequest {
+        action_type: "chat".to_string(),
+        data: ChatRequestData { message },
+    };
+
+    serde_json::ser::to_string(&chat_request).unwrap_or_else(|_op| {
+        error!("Couldn't convert Chat Request struct to string");
+        "".to_string()
+    })
+}
@@ -1,7 +1,7 @@
 use std::{io::ErrorKind, net::TcpStream};
 
 use bevy::{
-    ecs::world::CommandQueue,
+    ecs::{system::SystemParam, world::CommandQueue},
     prelude::*,
     tasks::{block_on, futures_lite::future, AsyncComputeTaskPool, Task},
 };
@@ -26,6 +26,15 @@ pub enum C2SActionTypes {
     #[strum(serialize = "interact", serialize = "i")]
     Interact,
 
+    #[strum(serialize = "emote", serialize = "em")]
+    Emote,
+
+    #[strum(serialize = "chat", serialize = "c")]
+    Chat,
+
+    #[strum(serialize = "leave", serialize = "l")]
+    Leave,
+
     #[strum(serialize = "empty", serialize = "e")]
     Empty, // used as a default in order to ignore invalid inputs without panicing
 }
@@ -43,6 +52,16 @@ pub enum S2CActionTypes {
     #[strum(serialize = "other_player_quacked", serialize = "opq")]
     OtherPlayerQuacked,
 
+    #[strum(serialize = "you_emoted", serialize = "ye")]
+    YouEmoted,
+    #[strum(serialize = "other_player_emoted", serialize = "ope")]
+    OtherPlayerEmoted,
+
+    #[strum(serialize = "you_chatted", serialize = "yc")]
+    YouChatted,
+    #[strum(serialize = "other_player_chatted", serialize = "opc")]
+    OtherPlayerChatted,
+
     #[strum(serialize = "you_moved", serialize = "ym")]
     YouMoved,
     #[strum(serialize = "other_player_moved", serialize = "opm")]
@@ -53,6 +72,20 @@ pub enum S2CActionTypes {
     #[strum(serialize = "other_player_got_crackers", serialize = "opgc")]
     OtherPlayerGotCrackers,
 
+    #[strum(serialize = "cracker_spawned", serialize = "cs")]
+    CrackerSpawned,
+    #[strum(serialize = "cracker_updated", serialize = "cu")]
+    CrackerUpdated,
+    #[strum(serialize = "cracker_despawned", serialize = "cd")]
+    CrackerDespawned,
+
+    #[strum(serialize = "npc_spawned", serialize = "ns")]
+    NpcSpawned,
+    #[strum(serialize = "npc_moved", serialize = "nm")]
+    NpcMoved,
+    #[strum(serialize = "npc_despawned", serialize = "nd")]
+    NpcDespawned,
+
     #[strum(serialize = "you_died", serialize = "yd")]
     YouDied,
     #[strum(serialize = "other_player_died", serialize = "opd")]
@@ -70,6 +103,7 @@ pub enum S2CActionTypes {
 
 #[derive(Debug, Deserialize, Clone)]
 pub struct GotCrackerResponseData {
+    pub cracker_id: String,
     pub player_uuid: String,
     pub player_friendly_name: String,
 
@@ -82,6 +116,11 @@ pub struct GotCrackerResponseData {
     pub old_cracker_point_value: u64,
     pub new_cracker_point_value: u64,
 
+    #[serde(default)]
+    pub old_cracker_kind: CrackerKind,
+    #[serde(default)]
+    pub new_cracker_kind: CrackerKind,
+
     pub new_player_score: u64,
 }
 
@@ -91,12 +130,24 @@ pub(super) fn plugin(app: &mut App) {
     app.add_event::<OtherPlayerJoinedWsReceived>();
     app.add_event::<OtherPlayerMovedWsReceived>();
     app.add_event::<OtherPlayerQuackedWsReceived>();
+    app.add_event::<OtherPlayerEmotedWsReceived>();
+    app.add_event::<OtherPlayerChattedWsReceived>();
+    app.add_event::<YouDiedWsReceived>();
+    app.add_event::<OtherPlayerDiedWsReceived>();
     app.add_event::<MoveCrackersBevyEvent>();
+    app.add_event::<SpawnCrackerBevyEvent>();
+    app.add_event::<UpdateCrackerBevyEvent>();
+    app.add_event::<DespawnCrackerBevyEvent>();
+    app.add_event::<SpawnNpcBevyEvent>();
+    app.add_event::<MoveNpcBevyEvent>();
+    app.add_event::<DespawnNpcBevyEvent>();
     app.add_event::<UpdateYourScoreBevyEvent>();
     app.add_event::<UpdateLeaderboardBevyEvent>();
     app.add_event::<UserDisconnectedBevyEvent>();
 
     app.add_systems(Startup, actually_connect);
+    // Leaving the game closes the connection, so open a new one to play again.
+    app.add_systems(OnEnter(Screen::Gameplay), reconnect);
     app.add_systems(Update, setup_connection);
     app.add_systems(Update, handle_tasks);
     app.add_systems(Update, receive_ws_msg);
@@ -120,14 +171,53 @@ pub struct YouJoinedWsReceived {
     pub data: Value,
 }
 
+/// A cracker was eaten and moved somewhere else.
 #[derive(Event, Debug, Clone, Deserialize)]
 pub struct MoveCrackersBevyEvent {
+    pub cracker_id: String,
     pub x_position: f32,
     pub y_position: f32,
     pub points: u64,
+    /// The kind of the cracker once it's moved.
+    pub kind: CrackerKind,
+    /// The kind of the cracker that was eaten.
+    pub eaten_kind: CrackerKind,
     pub you_got_crackers: bool,
 }
 
+#[derive(Event, Debug, Clone)]
+pub struct SpawnCrackerBevyEvent {
+    pub data: CrackerData,
+}
+
+/// A cracker moved or changed value without anyone eating it.
+#[derive(Event, Debug, Clone)]
+pub struct UpdateCrackerBevyEvent {
+    pub data: CrackerData,
+}
+
+#[derive(Event, Debug, Clone, Deserialize)]
+pub struct DespawnCrackerBevyEvent {
+    pub cracker_id: String,
+}
+
+#[derive(Event, Debug, Clone)]
+pub struct SpawnNpcBevyEvent {
+    pub data: NpcData,
+}
+
+#[derive(Event, Debug, Clone, Deserialize)]
+pub struct MoveNpcBevyEvent {
+    pub npc_id: String,
+    pub x_position: f32,
+    pub y_position: f32,
+}
+
+#[derive(Event, Debug, Clone, Deserialize)]
+pub struct DespawnNpcBevyEvent {
+    pub npc_id: String,
+}
+
 #[derive(Event, Debug, Clone, Deserialize)]
 pub struct UpdateLeaderboardBevyEvent {
     pub data: Value,
@@ -153,6 +243,26 @@ pub struct OtherPlayerQuackedWsReceived {
     pub data: Value,
 }
 
+#[derive(Event, Debug, Clone)]
+pub struct OtherPlayerEmotedWsReceived {
+    pub data: Value,
+}
+
+#[derive(Event, Debug, Clone)]
+pub struct OtherPlayerChattedWsReceived {
+    pub data: Value,
+}
+
+#[derive(Event, Debug, Clone)]
+pub struct YouDiedWsReceived {
+    pub data: Value,
+}
+
+#[derive(Event, Debug, Clone)]
+pub struct OtherPlayerDiedWsReceived {
+    pub data: Value,
+}
+
 #[derive(Event, Debug, Clone)]
 pub struct OtherPlayerMovedWsReceived {
     pub data: Value,
@@ -173,9 +283,26 @@ fn actually_connect(
 
 use thiserror::Error;
 
-use crate::demo::other_player::DuckDirection;
+use crate::{
+    audio::{AudioChannel, PlaySfx},
+    demo::other_player::DuckDirection,
+    screens::Screen,
+};
 
-use super::{cracker::YouGotCrackerSoundFx, other_player::OtherPlayerData};
+use super::{
+    cracker::{CrackerData, CrackerKind, YouGotCrackerSoundFx},
+    npc::NpcData,
+    other_player::OtherPlayerData,
+};
+
+fn reconnect(
+    connection_query: Query<(), Or<(With<WebSocketClient>, With<WebSocketConnectionSetupTask>)>>,
+    mut ev_connect: EventWriter<WebSocketConnectionEvents>,
+) {
+    if connection_query.is_empty() {
+        ev_connect.send(WebSocketConnectionEvents::SetupConnection);
+    }
+}
 
 #[derive(Error, Debug)]
 enum ConnectionSetupError {
@@ -230,19 +357,50 @@ fn setup_connection(
     }
 }
 
+/// Writers for players talking to each other, grouped to stay under Bevy's
+/// limit on system parameters.
+#[derive(SystemParam)]
+struct SocialEventWriters<'w> {
+    other_player_quacked: EventWriter<'w, OtherPlayerQuackedWsReceived>,
+    other_player_emoted: EventWriter<'w, OtherPlayerEmotedWsReceived>,
+    other_player_chatted: EventWriter<'w, OtherPlayerChattedWsReceived>,
+}
+
+#[derive(SystemParam)]
+struct CrackerEventWriters<'w> {
+    spawn: EventWriter<'w, SpawnCrackerBevyEvent>,
+    update: EventWriter<'w, UpdateCrackerBevyEvent>,
+    despawn: EventWriter<'w, DespawnCrackerBevyEvent>,
+}
+
+#[derive(SystemParam)]
+struct NpcEventWriters<'w> {
+    spawn: EventWriter<'w, SpawnNpcBevyEvent>,
+    moved: EventWriter<'w, MoveNpcBevyEvent>,
+    despawn: EventWriter<'w, DespawnNpcBevyEvent>,
+}
+
+#[derive(SystemParam)]
+struct DeathEventWriters<'w> {
+    you_died: EventWriter<'w, YouDiedWsReceived>,
+    other_player_died: EventWriter<'w, OtherPlayerDiedWsReceived>,
+}
+
 fn receive_ws_msg(
-    mut commands: Commands,
+    mut play_sfx: EventWriter<PlaySfx>,
     mut q: Query<(&mut WebSocketClient,)>,
     mut bevy_event_writer_you_joined: EventWriter<YouJoinedWsReceived>,
     mut bevy_event_writer_other_player_joined: EventWriter<OtherPlayerJoinedWsReceived>,
-    mut bevy_event_writer_other_player_quacked: EventWriter<OtherPlayerQuackedWsReceived>,
+    mut bevy_event_writers_social: SocialEventWriters,
+    mut bevy_event_writers_death: DeathEventWriters,
+    mut bevy_event_writers_npc: NpcEventWriters,
     mut bevy_event_writer_other_player_moved: EventWriter<OtherPlayerMovedWsReceived>,
     mut bevy_event_writer_move_crackers: EventWriter<MoveCrackersBevyEvent>,
+    mut bevy_event_writers_cracker: CrackerEventWriters,
     mut bevy_event_writer_user_disconnected: EventWriter<UserDisconnectedBevyEvent>,
     mut bevy_event_writer_update_your_score: EventWriter<UpdateYourScoreBevyEvent>,
     mut bevy_event_writer_update_leaderboard: EventWriter<UpdateLeaderboardBevyEvent>,
     audio: Res<YouGotCrackerSoundFx>,
-    audio_assets: Res<Assets<AudioSource>>,
 ) {
     for (mut client,) in q.iter_mut() {
         match client.0 .0.read() {
@@ -294,11 +452,31 @@ fn receive_ws_msg(
                         info!("Received 'YouQuacked' message from ws server!");
                     }
                     S2CActionTypes::OtherPlayerQuacked => {
-                        bevy_event_writer_other_player_quacked.send(OtherPlayerQuackedWsReceived {
+                        bevy_event_writers_social.other_player_quacked.send(OtherPlayerQuackedWsReceived {
                             data: generic_msg.data,
                         });
                         info!("Received 'OtherPlayerQuacked' message from ws server!");
                     }
+                    S2CActionTypes::YouEmoted => {
+                        // Basically ignored (bc the emote already played before sending to server)
+                        info!("Received 'YouEmoted' message from ws server!");
+                    }
+                    S2CActionTypes::OtherPlayerEmoted => {
+                        bevy_event_writers_social.other_player_emoted.send(OtherPlayerEmotedWsReceived {
+                            data: generic_msg.data,
+                        });
+                        info!("Received 'OtherPlayerEmoted' message from ws server!");
+                    }
+                    S2CActionTypes::YouChatted => {
+                        // Basically ignored (bc the message was already shown before sending to server)
+                        info!("Received 'YouChatted' message from ws server!");
+                    }
+                    S2CActionTypes::OtherPlayerChatted => {
+                        bevy_event_writers_social.other_player_chatted.send(OtherPlayerChattedWsReceived {
+                            data: generic_msg.data,
+                        });
+                        info!("Received 'OtherPlayerChatted' message from ws server!");
+                    }
                     S2CActionTypes::YouMoved => {
                         // Basically ignored (bc you already moved before sending to server)
                         info!("Received 'YouMoved' message from ws server!");
@@ -311,21 +489,17 @@ fn receive_ws_msg(
                     }
                     S2CActionTypes::YouGotCrackers => {
                         // Handle "YouGotCrackersMsg" from server.
-                        let you_got_crackers_msg_data =
-                            serde_json::from_value(generic_msg.data.clone()).unwrap_or_else(|op| {
-                                info!("Failed to parse incoming websocket message: {}", op);
-                                GotCrackerResponseData {
-                                    player_uuid: "error".to_string(),
-                                    player_friendly_name: "error".to_string(),
-                                    old_cracker_x_position: 0.,
-                                    old_cracker_y_position: 0.,
-                                    new_cracker_x_position: 0.,
-                                    new_cracker_y_position: 0.,
-                                    old_cracker_point_value: 0,
-                                    new_cracker_point_value: 0,
-                                    new_player_score: 0,
-                                }
-                            });
+                        // Without the data there's no cracker to move or score to
+                        // update, so the message is dropped.
+                        let you_got_crackers_msg_data = match serde_json::from_value::<GotCrackerResponseData>(
+                            generic_msg.data,
+                        ) {
+                            Ok(data) => data,
+                            Err(op) => {
+                                warn!("Failed to parse incoming websocket message: {}", op);
+                                continue;
+                            }
+                        };
 
                         info!(
                             "Received 'YouGotCrackers' message from ws server, new score: {}",
@@ -333,22 +507,19 @@ fn receive_ws_msg(
                         );
 
                         // Play special you got crackers sound
-                        if let Some(_) = audio_assets.get(&audio.sound_handle) {
-                            // Spawn an audio source to play the sound
-                            commands.spawn(AudioSourceBundle {
-                                source: audio.sound_handle.clone(),
-                                ..Default::default()
-                            });
-                            println!("Playing your quack sound.");
-                        } else {
-                            println!("Audio not loaded yet.");
-                        }
+                        play_sfx.send(PlaySfx::new(
+                            AudioChannel::Sfx,
+                            audio.sound_handle.clone(),
+                        ));
 
                         // --> send event for crackers to move
                         bevy_event_writer_move_crackers.send(MoveCrackersBevyEvent {
+                            cracker_id: you_got_crackers_msg_data.cracker_id,
                             x_position: you_got_crackers_msg_data.new_cracker_x_position,
                             y_position: you_got_crackers_msg_data.new_cracker_y_position,
                             points: you_got_crackers_msg_data.new_cracker_point_value,
+                            kind: you_got_crackers_msg_data.new_cracker_kind,
+                            eaten_kind: you_got_crackers_msg_data.old_cracker_kind,
                             you_got_crackers: true,
                         });
 
@@ -359,35 +530,99 @@ fn receive_ws_msg(
                     }
                     S2CActionTypes::OtherPlayerGotCrackers => {
                         // Handle "OtherPlayerGotCrackers" from server.
-                        let other_player_got_crackers_msg_data =
-                            serde_json::from_value(generic_msg.data.clone()).unwrap_or_else(|op| {
-                                info!("Failed to parse incoming websocket message: {}", op);
-                                GotCrackerResponseData {
-                                    player_uuid: "error".to_string(),
-                                    player_friendly_name: "error".to_string(),
-                                    old_cracker_x_position: 0.,
-                                    old_cracker_y_position: 0.,
-                                    new_cracker_x_position: 0.,
-                                    new_cracker_y_position: 0.,
-                                    old_cracker_point_value: 0,
-                                    new_cracker_point_value: 0,
-                                    new_player_score: 0,
-                                }
-                            });
+                        let other_player_got_crackers_msg_data = match serde_json::from_value::<GotCrackerResponseData>(
+                            generic_msg.data,
+                        ) {
+                            Ok(data) => data,
+                            Err(op) => {
+                                warn!("Failed to parse incoming websocket message: {}", op);
+                                continue;
+                            }
+                        };
 
                         // --> send event for crackers to move
                         bevy_event_writer_move_crackers.send(MoveCrackersBevyEvent {
+                            cracker_id: other_player_got_crackers_msg_data.cracker_id,
                             x_position: other_player_got_crackers_msg_data.new_cracker_x_position,
                             y_position: other_player_got_crackers_msg_data.new_cracker_y_position,
                             points: other_player_got_crackers_msg_data.new_cracker_point_value,
+                            kind: other_player_got_crackers_msg_data.new_cracker_kind,
+                            eaten_kind: other_player_got_crackers_msg_data.old_cracker_kind,
                             you_got_crackers: false,
                         });
                         info!("Received 'OtherPlayerGotCrackers' message from ws server!");
                     }
+                    S2CActionTypes::CrackerSpawned | S2CActionTypes::CrackerUpdated => {
+                        match serde_json::from_value::<CrackerData>(generic_msg.data) {
+                            Ok(data) => {
+                                if generic_msg.action_type == S2CActionTypes::CrackerSpawned {
+                                    bevy_event_writers_cracker.spawn
+                                        .send(SpawnCrackerBevyEvent { data });
+                                } else {
+                                    bevy_event_writers_cracker.update
+                                        .send(UpdateCrackerBevyEvent { data });
+                                }
+                            }
+                            Err(op) => {
+                                info!("Failed to parse incoming websocket message: {}", op);
+                            }
+                        }
+                        info!("Received '{:?}' message from ws server!", generic_msg.action_type);
+                    }
+                    S2CActionTypes::CrackerDespawned => {
+                        match serde_json::from_value::<DespawnCrackerBevyEvent>(generic_msg.data) {
+                            Ok(event) => {
+                                bevy_event_writers_cracker.despawn.send(event);
+                            }
+                            Err(op) => {
+                                info!("Failed to parse incoming websocket message: {}", op);
+                            }
+                        }
+                        info!("Received 'CrackerDespawned' message from ws server!");
+                    }
+                    S2CActionTypes::NpcSpawned => {
+                        match serde_json::from_value::<NpcData>(generic_msg.data) {
+                            Ok(data) => {
+                                bevy_event_writers_npc.spawn.send(SpawnNpcBevyEvent { data });
+                            }
+                            Err(op) => {
+                                info!("Failed to parse incoming websocket message: {}", op);
+                            }
+                        }
+                        info!("Received 'NpcSpawned' message from ws server!");
+                    }
+                    S2CActionTypes::NpcMoved => {
+                        match serde_json::from_value::<MoveNpcBevyEvent>(generic_msg.data) {
+                            Ok(event) => {
+                                bevy_event_writers_npc.moved.send(event);
+                            }
+                            Err(op) => {
+                                info!("Failed to parse incoming websocket message: {}", op);
+                            }
+                        }
+                        info!("Received 'NpcMoved' message from ws server!");
+                    }
+                    S2CActionTypes::NpcDespawned => {
+                        match serde_json::from_value::<DespawnNpcBevyEvent>(generic_msg.data) {
+                            Ok(event) => {
+                                bevy_event_writers_npc.despawn.send(event);
+                            }
+                            Err(op) => {
+                                info!("Failed to parse incoming websocket message: {}", op);
+                            }
+                        }
+                        info!("Received 'NpcDespawned' message from ws server!");
+                    }
                     S2CActionTypes::YouDied => {
+                        bevy_event_writers_death.you_died.send(YouDiedWsReceived {
+                            data: generic_msg.data,
+                        });
                         info!("Received 'YouDied' message from ws server!");
                     }
                     S2CActionTypes::OtherPlayerGotDied => {
+                        bevy_event_writers_death.other_player_died.send(OtherPlayerDiedWsReceived {
+                            data: generic_msg.data,
+                        });
                         info!("Received 'OtherPlayerGotDied' message from ws server!");
                     }
                     S2CActionTypes::UserDisconnected => {
@@ -419,9 +654,9 @@ fn receive_ws_msg(
 /// removes the task component from the entity.
 fn handle_tasks(
     mut commands: Commands,
-    mut transform_tasks: Query<&mut WebSocketConnectionSetupTask>,
+    mut transform_tasks: Query<(Entity, &mut WebSocketConnectionSetupTask)>,
 ) {
-    for mut task in &mut transform_tasks {
+    for (entity, mut task) in &mut transform_tasks {
         if let Some(result) = block_on(future::poll_once(&mut task.0)) {
             // append the returned command queue to have it execute later
             match result {
@@ -430,6 +665,9 @@ fn handle_tasks(
                 }
                 Err(e) => {
                     info!("Connection failed with: {e:?}");
+                    // Don't poll the finished task again, and let the next
+                    // game try to connect afresh.
+                    commands.entity(entity).despawn();
                 }
             }
         }
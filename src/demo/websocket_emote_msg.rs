@@ -0,0 +1,62 @@
+use std::io::ErrorKind;
+use bevy::prelude::*;
+use tungstenite::Message;
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_event::<EmoteRequestEvent>();
+    app.add_systems(Update, emote_request_bevy_event_listener);
+}
+
+use super::{emote::Emote, websocket_connect::WebSocketClient};
+
+/// Tell the server our duck emoted, so it can show it to everyone else.
+#[derive(Event)]
+pub struct EmoteRequestEvent {
+    pub emote: Emote,
+}
+
+// Listens for bevy events for ws messages and fires them off to the server
+fn emote_request_bevy_event_listener(
+    mut ev_emote_request: EventReader<EmoteRequestEvent>,
+    mut entities_with_client: Query<(&mut WebSocketClient,)>,
+) {
+    for ev in ev_emote_request.read() {
+        debug!("heard emote request bevy event");
+        for mut client in entities_with_client.iter_mut() {
+            debug!("sending emote request ws msg");
+            let message = build_emote_request_msg(ev.emote);
+
+            match client.0 .0 .0.send(Message::text(message)) {
+                Ok(_) => info!("Emote request ws msg successfully sent to server!"),
+                Err(tungstenite::Error::Io(e)) if e.kind() == ErrorKind::WouldBlock => { /* ignore */
+                }
+                Err(e) => {
+                    warn!("Could not send the message: {e:?}");
+                }
+            }
+        }
+    }
+}
+
+#[derive(serde::Serialize)]
+struct EmoteRequestData {
+    emote: Emote,
+}
+
+#[derive(serde::Serialize)]
+struct EmoteRequest {
+    action_type: String,
+    data: EmoteRequestData,
+}
+
+fn build_emote_request_msg(emote: Emote) -> String {
+    let emote_request = EmoteRequest {
+        action_type: "emote".to_string(),
+        data: EmoteRequestData { emote },
+    };
+
+    serde_json::ser::to_string(&emote_request).unwrap_or_else(|_op| {
+        error!("Couldn't convert Emote Request struct to string");
+        "".to_string()
+    })
+}
@@ -0,0 +1,63 @@
+use std::io::ErrorKind;
+use bevy::prelude::*;
+use tungstenite::Message;
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_event::<InteractRequestEvent>();
+    app.add_systems(Update, interact_request_bevy_event_listener);
+}
+
+use super::websocket_connect::WebSocketClient;
+
+/// Ask the server to pick up the cracker with this id. The server checks that
+/// it's actually in range.
+#[derive(Event)]
+pub struct InteractRequestEvent {
+    pub cracker_id: String,
+}
+
+// Listens for bevy events for ws messages and fires them off to the server
+fn interact_request_bevy_event_listener(
+    mut ev_interact_request: EventReader<InteractRequestEvent>,
+    mut entities_with_client: Query<(&mut WebSocketClient,)>,
+) {
+    for ev in ev_interact_request.read() {
+        debug!("heard interact request bevy event");
+        for mut client in entities_with_client.iter_mut() {
+            debug!("sending interact request ws msg");
+            let message = build_interact_request_msg(ev.cracker_id.clone());
+
+            match client.0 .0 .0.send(Message::text(message)) {
+                Ok(_) => info!("Interact request ws msg successfully sent to server!"),
+                Err(tungstenite::Error::Io(e)) if e.kind() == ErrorKind::WouldBlock => { /* ignore */
+                }
+                Err(e) => {
+                    warn!("Could not send the message: {e:?}");
+                }
+            }
+        }
+    }
+}
+
+#[derive(serde::Serialize)]
+struct InteractRequestData {
+    cracker_id: String,
+}
+
+#[derive(serde::Serialize)]
+struct InteractRequest {
+    action_type: String,
+    data: InteractRequestData,
+}
+
+fn build_interact_request_msg(cracker_id: String) -> String {
+    let interact_request = InteractRequest {
+        action_type: "interact".to_string(),
+        data: InteractRequestData { cracker_id },
+    };
+
+    serde_json::ser::to_string(&interact_request).unwrap_or_else(|_op| {
+        error!("Couldn't convert Interact Request struct to string");
+        "".to_string()
+    })
+}
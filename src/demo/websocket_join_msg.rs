@@ -20,21 +20,36 @@ pub(super) fn plugin(app: &mut App) {
     app.add_systems(Update, join_request_bevy_event_listener);
 }
 
-use super::websocket_connect::WebSocketClient;
+use super::{websocket_connect::WebSocketClient, websocket_leave_msg::LeaveRequestEvent};
 
+/// Friendly name and duck colour to join the game with.
 #[derive(Event)]
-pub struct JoinRequestEvent(pub String);
+pub struct JoinRequestEvent(pub String, pub String);
 
-// Listens for bevy events for ws messages and fires them off to the server
+// Listens for bevy events for ws messages and fires them off to the server.
+// After leaving a game the connection is reopened when you play again, so the
+// join request waits until there's a client to send it on.
 fn join_request_bevy_event_listener(
     mut ev_join_request: EventReader<JoinRequestEvent>,
+    mut ev_leave_request: EventReader<LeaveRequestEvent>,
+    mut pending: Local<Option<(String, String)>>,
     mut entities_with_client: Query<(&mut WebSocketClient,)>,
 ) {
     for ev in ev_join_request.read() {
         println!("heard join request bevy event");
+        *pending = Some((ev.0.clone(), ev.1.clone()));
+    }
+    // Leaving before the connection was up.
+    if ev_leave_request.read().last().is_some() {
+        *pending = None;
+    }
+    if entities_with_client.is_empty() {
+        return;
+    }
+    if let Some((friendly_name, color)) = pending.take() {
         for mut client in entities_with_client.iter_mut() {
             println!("sending join request ws msg");
-            let message = build_join_request_msg(ev.0.clone());
+            let message = build_join_request_msg(friendly_name.clone(), color.clone());
 
             match client.0 .0 .0.send(Message::text(message)) {
                 Ok(_) => info!("Join request ws msg successfully sent to server!"),
@@ -51,6 +66,7 @@ fn join_request_bevy_event_listener(
 #[derive(serde::Serialize)]
 struct JoinRequestData {
     friendly_name: String,
+    color: String,
 }
 
 #[derive(serde::Serialize)]
@@ -59,11 +75,12 @@ struct JoinRequest {
     data: JoinRequestData,
 }
 
-fn build_join_request_msg(friendly_name: String) -> String {
+fn build_join_request_msg(friendly_name: String, color: String) -> String {
     let join_request_hardcoded = JoinRequest {
         action_type: "join".to_string(),
         data: JoinRequestData {
             friendly_name: friendly_name,
+            color,
         },
     };
 
@@ -0,0 +1,140 @@
+use std::io::ErrorKind;
+use bevy::prelude::*;
+use tungstenite::Message;
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_event::<LeaveRequestEvent>();
+    app.add_systems(Update, (leave_request_bevy_event_listener, finish_closing).chain());
+}
+
+use super::websocket_connect::WebSocketClient;
+
+/// How long to wait for the server to answer the close frame before giving up
+/// on it, in seconds.
+const CLOSE_TIMEOUT_SECONDS: f32 = 1.0;
+
+/// Leave the game. The server removes your duck, and the connection is closed.
+/// A new one is opened the next time you play.
+#[derive(Event)]
+pub struct LeaveRequestEvent;
+
+/// A connection that's been closed on our side, kept until the server
+/// acknowledges the close frame or the timeout runs out. It's no longer a
+/// [`WebSocketClient`], so nothing else sends on it or reads from it.
+#[derive(Component)]
+struct ClosingWebSocket {
+    client: WebSocketClient,
+    timeout: Timer,
+}
+
+// Listens for bevy events for ws messages and fires them off to the server
+fn leave_request_bevy_event_listener(
+    mut commands: Commands,
+    mut ev_leave_request: EventReader<LeaveRequestEvent>,
+    mut entities_with_client: Query<(Entity, &mut WebSocketClient)>,
+) {
+    if ev_leave_request.read().last().is_none() {
+        return;
+    }
+    debug!("heard leave request bevy event");
+
+    for (entity, mut client) in entities_with_client.iter_mut() {
+        let message = build_leave_request_msg();
+
+        match client.0 .0.send(Message::text(message)) {
+            Ok(_) => info!("Leave request ws msg successfully sent to server!"),
+            Err(tungstenite::Error::Io(e)) if e.kind() == ErrorKind::WouldBlock => { /* ignore */ }
+            Err(e) => {
+                warn!("Could not send the message: {e:?}");
+            }
+        }
+
+        // Queues the close frame. The socket is non-blocking, so it's flushed
+        // by `finish_closing` over the next frames.
+        match client.0 .0.close(None) {
+            Ok(_) => info!("Closing the websocket connection"),
+            Err(tungstenite::Error::Io(e)) if e.kind() == ErrorKind::WouldBlock => { /* ignore */ }
+            Err(e) => {
+                warn!("Could not close the connection: {e:?}");
+            }
+        }
+
+        commands.add(move |world: &mut World| {
+            let Some(mut entity) = world.get_entity_mut(entity) else {
+                return;
+            };
+            if let Some(client) = entity.take::<WebSocketClient>() {
+                entity.insert(ClosingWebSocket {
+                    client,
+                    timeout: Timer::from_seconds(CLOSE_TIMEOUT_SECONDS, TimerMode::Once),
+                });
+            }
+        });
+    }
+}
+
+/// Flushes the close frame and reads until the server closes its side, then
+/// drops the connection.
+fn finish_closing(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut closing_query: Query<(Entity, &mut ClosingWebSocket)>,
+) {
+    for (entity, mut closing) in &mut closing_query {
+        closing.timeout.tick(time.delta());
+        let socket = &mut closing.client.0 .0;
+
+        let closed = loop {
+            if let Err(e) = socket.flush() {
+                break close_result(e);
+            }
+            // Anything the server sends in the meantime is ignored.
+            if let Err(e) = socket.read() {
+                break close_result(e);
+            }
+        };
+
+        match closed {
+            Some(()) => info!("Closed the websocket connection"),
+            None if closing.timeout.finished() => {
+                warn!("The server didn't acknowledge the close in time, dropping the connection");
+            }
+            None => continue,
+        }
+        commands.entity(entity).despawn();
+    }
+}
+
+/// `Some` once the connection is done with, `None` while it's still waiting
+/// on the server.
+fn close_result(e: tungstenite::Error) -> Option<()> {
+    match e {
+        tungstenite::Error::Io(e) if e.kind() == ErrorKind::WouldBlock => None,
+        tungstenite::Error::ConnectionClosed | tungstenite::Error::AlreadyClosed => Some(()),
+        e => {
+            warn!("Error while closing the connection: {e:?}");
+            Some(())
+        }
+    }
+}
+
+#[derive(serde::Serialize)]
+struct LeaveRequestData {}
+
+#[derive(serde::Serialize)]
+struct LeaveRequest {
+    action_type: String,
+    data: LeaveRequestData,
+}
+
+fn build_leave_request_msg() -> String {
+    let leave_request = LeaveRequest {
+        action_type: "leave".to_string(),
+        data: LeaveRequestData {},
+    };
+
+    serde_json::ser::to_string(&leave_request).unwrap_or_else(|_op| {
+        error!("Couldn't convert Leave Request struct to string");
+        "".to_string()
+    })
+}
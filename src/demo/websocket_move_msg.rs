@@ -9,8 +9,10 @@ pub(super) fn plugin(app: &mut App) {
 
 pub(crate) use super::websocket_connect::WebSocketClient;
 
+/// How far the duck moved, and whether it was dashing. The server checks the
+/// distance against the duck's speed, which is higher while dashing.
 #[derive(Event)]
-pub struct MoveRequestEvent(pub f32, pub f32);
+pub struct MoveRequestEvent(pub f32, pub f32, pub bool);
 
 // Listens for bevy events for ws messages and fires them off to the server
 fn move_request_bevy_event_listener(
@@ -21,7 +23,7 @@ fn move_request_bevy_event_listener(
         println!("heard move request bevy event");
         for mut client in entities_with_client.iter_mut() {
             println!("sending move request ws msg");
-            let message = build_move_request_msg(ev.0.clone(), ev.1.clone());
+            let message = build_move_request_msg(ev.0, ev.1, ev.2);
 
             match client.0 .0 .0.send(Message::text(message)) {
                 Ok(_) => info!("Join request ws msg successfully sent to server!"),
@@ -39,6 +41,7 @@ fn move_request_bevy_event_listener(
 struct MoveRequestData {
     x_direction: f32,
     y_direction: f32,
+    dashing: bool,
 }
 
 #[derive(serde::Serialize)]
@@ -47,12 +50,13 @@ struct MoveRequest {
     data: MoveRequestData,
 }
 
-fn build_move_request_msg(x_direction: f32, y_direction: f32) -> String {
+fn build_move_request_msg(x_direction: f32, y_direction: f32, dashing: bool) -> String {
     let join_request_hardcoded = MoveRequest {
         action_type: "move".to_string(),
         data: MoveRequestData {
             x_direction,
             y_direction,
+            dashing,
         },
     };
 
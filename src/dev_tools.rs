@@ -5,11 +5,18 @@ use bevy::{
         states::log_transitions,
         ui_debug_overlay::{DebugUiPlugin, UiDebugOptions},
     },
-    input::common_conditions::input_just_pressed,
     prelude::*,
 };
 
-use crate::screens::Screen;
+use crate::{
+    audio::{
+        voices::{voice_limit, VoiceStats},
+        AudioChannel,
+    },
+    demo::collision::{Collider, CollisionLayer},
+    input::input_map::{action_just_pressed, InputAction},
+    screens::Screen,
+};
 
 pub(super) fn plugin(app: &mut App) {
     // Log `Screen` state transitions.
@@ -19,12 +26,113 @@ pub(super) fn plugin(app: &mut App) {
     app.add_plugins(DebugUiPlugin);
     app.add_systems(
         Update,
-        toggle_debug_ui.run_if(input_just_pressed(TOGGLE_KEY)),
+        toggle_debug_ui.run_if(action_just_pressed(InputAction::ToggleDebugUi)),
     );
-}
 
-const TOGGLE_KEY: KeyCode = KeyCode::Backquote;
+    // Outline colliders while the debug overlay is on.
+    app.add_systems(
+        Update,
+        draw_colliders.run_if(|options: Res<UiDebugOptions>| options.enabled),
+    );
+
+    // Show the sound budget counters while the debug overlay is on.
+    app.add_systems(Startup, spawn_voice_stats);
+    app.add_systems(Update, update_voice_stats);
+
+    // Log the collision layer in the format the server validates moves with.
+    app.add_systems(
+        Update,
+        log_collision_layer.run_if(resource_changed::<CollisionLayer>),
+    );
+}
 
 fn toggle_debug_ui(mut options: ResMut<UiDebugOptions>) {
     options.toggle();
 }
+
+fn draw_colliders(mut gizmos: Gizmos, colliders: Query<(&Collider, &GlobalTransform)>) {
+    for (collider, transform) in &colliders {
+        let position = transform.translation().truncate();
+        match *collider {
+            Collider::Aabb { half_size } => {
+                gizmos.rect_2d(position, 0.0, half_size * 2.0, Color::WHITE);
+            }
+            Collider::Circle { radius } => {
+                gizmos.circle_2d(position, radius, Color::WHITE);
+            }
+        }
+    }
+}
+
+#[derive(Component)]
+struct VoiceStatsText;
+
+fn spawn_voice_stats(mut commands: Commands) {
+    commands.spawn((
+        Name::new("Voice stats"),
+        VoiceStatsText,
+        TextBundle {
+            text: Text::from_section(
+                "",
+                TextStyle {
+                    font_size: 16.0,
+                    color: Color::WHITE,
+                    ..default()
+                },
+            ),
+            style: Style {
+                position_type: PositionType::Absolute,
+                left: Val::Px(8.0),
+                bottom: Val::Px(8.0),
+                ..default()
+            },
+            background_color: Color::srgba(0.0, 0.0, 0.0, 0.6).into(),
+            visibility: Visibility::Hidden,
+            ..default()
+        },
+        ZIndex::Global(100),
+    ));
+}
+
+fn update_voice_stats(
+    options: Res<UiDebugOptions>,
+    stats: Res<VoiceStats>,
+    mut text_query: Query<(&mut Text, &mut Visibility), With<VoiceStatsText>>,
+) {
+    for (mut text, mut visibility) in &mut text_query {
+        visibility.set_if_neq(if options.enabled {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        });
+        if !options.enabled || !(options.is_changed() || stats.is_changed()) {
+            continue;
+        }
+
+        let voices = [
+            AudioChannel::Sfx,
+            AudioChannel::Ui,
+            AudioChannel::RemotePlayers,
+        ]
+        .map(|channel| {
+            format!(
+                "{channel:?} {}/{}",
+                stats.playing.get(&channel).copied().unwrap_or_default(),
+                voice_limit(channel).unwrap_or_default(),
+            )
+        })
+        .join("  ");
+        text.sections[0].value = format!(
+            "Voices: {voices}\nPlayed {}, stolen {}\nDropped: {} over budget, {} duplicates, {} out of range",
+            stats.played,
+            stats.stolen,
+            stats.dropped_over_budget,
+            stats.dropped_duplicate,
+            stats.dropped_out_of_range,
+        );
+    }
+}
+
+fn log_collision_layer(collision_layer: Res<CollisionLayer>) {
+    info!("Collision layer: {}", collision_layer.to_json());
+}
@@ -0,0 +1,188 @@
+//! Gamepad support.
+//!
+//! Any number of controllers can be plugged in or removed while the game is
+//! running. Only one of them is "active" at a time: the one a button was most
+//! recently pressed on. Its name is shown in the corner of the screen.
+
+use bevy::{
+    ecs::system::SystemParam,
+    input::gamepad::{GamepadButtonChangedEvent, GamepadConnectionEvent},
+    prelude::*,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<ActiveGamepad>();
+    app.add_systems(Startup, spawn_active_gamepad_text);
+    app.add_systems(
+        PreUpdate,
+        (track_gamepad_connections, switch_to_last_used_gamepad)
+            .chain()
+            .after(bevy::input::InputSystem),
+    );
+    app.add_systems(
+        Update,
+        update_active_gamepad_text.run_if(resource_changed::<ActiveGamepad>),
+    );
+}
+
+/// Stick deflection below this is treated as no input.
+pub const STICK_DEADZONE: f32 = 0.2;
+
+/// The gamepad whose input currently drives the game, if any is connected.
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ActiveGamepad(pub Option<Gamepad>);
+
+/// Reads input from the [`ActiveGamepad`] only, so that a second controller
+/// lying on the couch doesn't fight the one in use.
+#[derive(SystemParam)]
+pub struct ActiveGamepadInput<'w> {
+    active: Res<'w, ActiveGamepad>,
+    axes: Res<'w, Axis<GamepadAxis>>,
+    buttons: Res<'w, ButtonInput<GamepadButton>>,
+}
+
+impl ActiveGamepadInput<'_> {
+    /// Movement from the left stick, or the d-pad if the stick is at rest.
+    /// Used for menu navigation, where the d-pad is never rebound.
+    pub fn movement(&self) -> Vec2 {
+        let stick = self.left_stick();
+        if stick != Vec2::ZERO {
+            return stick;
+        }
+
+        let mut dpad = Vec2::ZERO;
+        if self.pressed(GamepadButtonType::DPadUp) {
+            dpad.y += 1.0;
+        }
+        if self.pressed(GamepadButtonType::DPadDown) {
+            dpad.y -= 1.0;
+        }
+        if self.pressed(GamepadButtonType::DPadLeft) {
+            dpad.x -= 1.0;
+        }
+        if self.pressed(GamepadButtonType::DPadRight) {
+            dpad.x += 1.0;
+        }
+        dpad
+    }
+
+    /// The left stick, with a radial deadzone. Past the deadzone it is
+    /// rescaled to start at zero.
+    pub fn left_stick(&self) -> Vec2 {
+        let Some(gamepad) = self.active.0 else {
+            return Vec2::ZERO;
+        };
+
+        let stick = Vec2::new(
+            self.axes
+                .get(GamepadAxis::new(gamepad, GamepadAxisType::LeftStickX))
+                .unwrap_or_default(),
+            self.axes
+                .get(GamepadAxis::new(gamepad, GamepadAxisType::LeftStickY))
+                .unwrap_or_default(),
+        );
+        apply_deadzone(stick)
+    }
+
+    pub fn pressed(&self, button_type: GamepadButtonType) -> bool {
+        self.active.0.is_some_and(|gamepad| {
+            self.buttons
+                .pressed(GamepadButton::new(gamepad, button_type))
+        })
+    }
+
+    pub fn just_pressed(&self, button_type: GamepadButtonType) -> bool {
+        self.active.0.is_some_and(|gamepad| {
+            self.buttons
+                .just_pressed(GamepadButton::new(gamepad, button_type))
+        })
+    }
+}
+
+fn apply_deadzone(stick: Vec2) -> Vec2 {
+    let length = stick.length();
+    if length < STICK_DEADZONE {
+        return Vec2::ZERO;
+    }
+    let rescaled = ((length - STICK_DEADZONE) / (1.0 - STICK_DEADZONE)).min(1.0);
+    stick / length * rescaled
+}
+
+fn track_gamepad_connections(
+    mut connection_events: EventReader<GamepadConnectionEvent>,
+    gamepads: Res<Gamepads>,
+    mut active: ResMut<ActiveGamepad>,
+) {
+    for e in connection_events.read() {
+        if e.connected() {
+            info!("Gamepad {} connected", e.gamepad.id);
+            if active.0.is_none() {
+                active.0 = Some(e.gamepad);
+            }
+        } else {
+            info!("Gamepad {} disconnected", e.gamepad.id);
+            if active.0 == Some(e.gamepad) {
+                // Fall back to any other controller that is still plugged in.
+                active.0 = gamepads.iter().find(|gamepad| *gamepad != e.gamepad);
+            }
+        }
+    }
+}
+
+fn switch_to_last_used_gamepad(
+    mut button_events: EventReader<GamepadButtonChangedEvent>,
+    mut active: ResMut<ActiveGamepad>,
+) {
+    for e in button_events.read() {
+        if e.value > 0.5 && active.0 != Some(e.gamepad) {
+            info!("Switching to gamepad {}", e.gamepad.id);
+            active.0 = Some(e.gamepad);
+        }
+    }
+}
+
+#[derive(Component)]
+struct ActiveGamepadText;
+
+fn spawn_active_gamepad_text(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.spawn((
+        Name::new("Active gamepad text"),
+        TextBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                left: Val::Percent(3.),
+                bottom: Val::Percent(3.),
+                ..Default::default()
+            },
+            text: Text::from_section(
+                "",
+                TextStyle {
+                    font: asset_server.load("FiraSans-Bold.ttf"),
+                    font_size: 18.0,
+                    color: Color::WHITE,
+                },
+            ),
+            ..Default::default()
+        },
+        ActiveGamepadText,
+    ));
+}
+
+fn update_active_gamepad_text(
+    active: Res<ActiveGamepad>,
+    gamepads: Res<Gamepads>,
+    mut text_query: Query<&mut Text, With<ActiveGamepadText>>,
+) {
+    let label = match active.0 {
+        Some(gamepad) => format!(
+            "Controller {}: {}",
+            gamepad.id + 1,
+            gamepads.name(gamepad).unwrap_or("Unknown")
+        ),
+        None => String::new(),
+    };
+
+    for mut text in &mut text_query {
+        text.sections[0].value = label.clone();
+    }
+}
@@ -0,0 +1,334 @@
+//! Action-based input.
+//!
+//! Gameplay code asks whether an [`InputAction`] is pressed instead of checking
+//! specific keys, so that players can rebind them on the controls screen. The
+//! [`InputMap`] is saved whenever it changes and restored on the next start.
+
+use bevy::{ecs::system::SystemParam, prelude::*, utils::HashMap};
+use serde::{Deserialize, Serialize};
+
+use crate::{input::gamepad::ActiveGamepadInput, persistence};
+
+pub(super) fn plugin(app: &mut App) {
+    app.insert_resource(InputMap::load());
+    app.init_resource::<KeyboardCaptured>();
+    app.init_resource::<GameplayInputBlocked>();
+    app.add_systems(Update, save_input_map.run_if(resource_changed::<InputMap>));
+}
+
+/// Things the player can do, independent of which key or button does it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum InputAction {
+    MoveUp,
+    MoveDown,
+    MoveLeft,
+    MoveRight,
+    Quack,
+    Dash,
+    EmoteWheel,
+    Chat,
+    Pause,
+    Interact,
+    ToggleMinimap,
+    ToggleDebugUi,
+}
+
+impl InputAction {
+    pub const ALL: [Self; 12] = [
+        Self::MoveUp,
+        Self::MoveDown,
+        Self::MoveLeft,
+        Self::MoveRight,
+        Self::Quack,
+        Self::Dash,
+        Self::EmoteWheel,
+        Self::Chat,
+        Self::Pause,
+        Self::Interact,
+        Self::ToggleMinimap,
+        Self::ToggleDebugUi,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::MoveUp => "Move up",
+            Self::MoveDown => "Move down",
+            Self::MoveLeft => "Move left",
+            Self::MoveRight => "Move right",
+            Self::Quack => "Quack",
+            Self::Dash => "Dash",
+            Self::EmoteWheel => "Emote wheel",
+            Self::Chat => "Chat",
+            Self::Pause => "Pause",
+            Self::Interact => "Interact",
+            Self::ToggleMinimap => "Minimap",
+            Self::ToggleDebugUi => "Debug overlay",
+        }
+    }
+
+    fn default_bindings(self) -> Bindings {
+        use GamepadButtonType as Pad;
+        let (keys, gamepad_buttons) = match self {
+            Self::MoveUp => (vec![KeyCode::KeyW, KeyCode::ArrowUp], vec![Pad::DPadUp]),
+            Self::MoveDown => (vec![KeyCode::KeyS, KeyCode::ArrowDown], vec![Pad::DPadDown]),
+            Self::MoveLeft => (vec![KeyCode::KeyA, KeyCode::ArrowLeft], vec![Pad::DPadLeft]),
+            Self::MoveRight => (
+                vec![KeyCode::KeyD, KeyCode::ArrowRight],
+                vec![Pad::DPadRight],
+            ),
+            Self::Quack => (vec![KeyCode::Space], vec![Pad::South]),
+            // East is the usual back button in menus, so it stays free for that.
+            Self::Dash => (
+                vec![KeyCode::ShiftLeft, KeyCode::ShiftRight],
+                vec![Pad::RightTrigger],
+            ),
+            Self::EmoteWheel => (vec![KeyCode::KeyQ], vec![Pad::North]),
+            Self::Chat => (vec![KeyCode::Enter], vec![]),
+            Self::Pause => (vec![KeyCode::Escape], vec![Pad::Start]),
+            Self::Interact => (vec![KeyCode::KeyE], vec![Pad::West]),
+            Self::ToggleMinimap => (vec![KeyCode::KeyM], vec![Pad::Select]),
+            Self::ToggleDebugUi => (vec![KeyCode::Backquote], vec![]),
+        };
+        Bindings {
+            keys,
+            gamepad_buttons,
+        }
+    }
+}
+
+/// The keys and gamepad buttons bound to one [`InputAction`]. Any of them
+/// triggers the action.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Bindings {
+    pub keys: Vec<KeyCode>,
+    pub gamepad_buttons: Vec<GamepadButtonType>,
+}
+
+#[derive(Resource, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InputMap(pub HashMap<InputAction, Bindings>);
+
+impl Default for InputMap {
+    fn default() -> Self {
+        Self(
+            InputAction::ALL
+                .into_iter()
+                .map(|action| (action, action.default_bindings()))
+                .collect(),
+        )
+    }
+}
+
+impl InputMap {
+    const SAVE_KEY: &'static str = "input_map";
+
+    /// Load the saved map, falling back to the defaults for any action that
+    /// wasn't saved (e.g. one added in a newer version of the game).
+    fn load() -> Self {
+        let mut map = Self::default();
+        if let Some(saved) = persistence::load(Self::SAVE_KEY) {
+            map.merge_saved(saved);
+        }
+        map
+    }
+
+    /// Takes each saved action's bindings on its own, so one that can't be
+    /// read any more (e.g. an action removed since) doesn't lose the rest.
+    fn merge_saved(&mut self, saved: HashMap<String, serde_json::Value>) {
+        for (name, bindings) in saved {
+            let action = serde_json::from_value::<InputAction>(name.clone().into());
+            let bindings = serde_json::from_value::<Bindings>(bindings);
+            match (action, bindings) {
+                (Ok(action), Ok(bindings)) => {
+                    self.0.insert(action, bindings);
+                }
+                (Err(err), _) | (_, Err(err)) => {
+                    warn!("Ignoring saved bindings for {name:?}: {err}");
+                }
+            }
+        }
+    }
+
+    pub fn bindings(&self, action: InputAction) -> &Bindings {
+        // Every action is filled in by `default` or `load`.
+        &self.0[&action]
+    }
+
+    pub fn bindings_mut(&mut self, action: InputAction) -> &mut Bindings {
+        self.0.entry(action).or_default()
+    }
+
+    /// Binds `key` to `action` alone. An action that already used it gets
+    /// `action`'s previous key instead, so no key does two things.
+    pub fn rebind_key(&mut self, action: InputAction, key: KeyCode) {
+        let previous = std::mem::replace(&mut self.bindings_mut(action).keys, vec![key]);
+        for (other, bindings) in &mut self.0 {
+            if *other != action {
+                swap_binding(&mut bindings.keys, key, &previous);
+            }
+        }
+    }
+
+    /// Like [`InputMap::rebind_key`], for a gamepad button.
+    pub fn rebind_gamepad_button(&mut self, action: InputAction, button: GamepadButtonType) {
+        let previous =
+            std::mem::replace(&mut self.bindings_mut(action).gamepad_buttons, vec![button]);
+        for (other, bindings) in &mut self.0 {
+            if *other != action {
+                swap_binding(&mut bindings.gamepad_buttons, button, &previous);
+            }
+        }
+    }
+}
+
+/// Replaces `taken` in `bound` with one of the `previous` bindings of the
+/// action that took it, or removes it if there's none to give.
+fn swap_binding<T: PartialEq + Copy>(bound: &mut Vec<T>, taken: T, previous: &[T]) {
+    let Some(index) = bound.iter().position(|binding| *binding == taken) else {
+        return;
+    };
+    match previous
+        .iter()
+        .find(|binding| **binding != taken && !bound.contains(binding))
+    {
+        Some(replacement) => bound[index] = *replacement,
+        None => {
+            bound.remove(index);
+        }
+    }
+}
+
+fn save_input_map(input_map: Res<InputMap>) {
+    if input_map.is_added() {
+        return;
+    }
+    persistence::save(InputMap::SAVE_KEY, &*input_map);
+}
+
+/// Set while a text field has keyboard focus, so that typing doesn't also
+/// move or quack.
+#[derive(Resource, Debug, Default, PartialEq, Eq)]
+pub struct KeyboardCaptured(pub bool);
+
+/// Set while a menu is open over gameplay, so that only
+/// [`InputAction::Pause`] gets through to the game.
+#[derive(Resource, Debug, Default, PartialEq, Eq)]
+pub struct GameplayInputBlocked(pub bool);
+
+/// Run condition that is false while [`GameplayInputBlocked`], for input that
+/// doesn't go through [`ActionInput`], like touches.
+pub fn gameplay_input_allowed(blocked: Res<GameplayInputBlocked>) -> bool {
+    !blocked.0
+}
+
+/// Reads [`InputAction`]s from the keyboard and the active gamepad through the
+/// current [`InputMap`]. The keyboard is ignored while it's [`KeyboardCaptured`],
+/// and everything but [`InputAction::Pause`] while [`GameplayInputBlocked`].
+#[derive(SystemParam)]
+pub struct ActionInput<'w> {
+    input_map: Res<'w, InputMap>,
+    keys: Res<'w, ButtonInput<KeyCode>>,
+    keyboard_captured: Res<'w, KeyboardCaptured>,
+    gameplay_blocked: Res<'w, GameplayInputBlocked>,
+    gamepad: ActiveGamepadInput<'w>,
+}
+
+impl ActionInput<'_> {
+    /// Whether gameplay input is blocked by a menu.
+    pub fn blocked(&self) -> bool {
+        self.gameplay_blocked.0
+    }
+
+    pub fn pressed(&self, action: InputAction) -> bool {
+        if self.blocked_action(action) {
+            return false;
+        }
+        let bindings = self.input_map.bindings(action);
+        (!self.keyboard_captured.0 && self.keys.any_pressed(bindings.keys.iter().copied()))
+            || bindings
+                .gamepad_buttons
+                .iter()
+                .any(|button| self.gamepad.pressed(*button))
+    }
+
+    pub fn just_pressed(&self, action: InputAction) -> bool {
+        if self.blocked_action(action) {
+            return false;
+        }
+        let bindings = self.input_map.bindings(action);
+        (!self.keyboard_captured.0 && self.keys.any_just_pressed(bindings.keys.iter().copied()))
+            || bindings
+                .gamepad_buttons
+                .iter()
+                .any(|button| self.gamepad.just_pressed(*button))
+    }
+
+    /// Whether an action's key was just pressed, even while the keyboard is
+    /// [`KeyboardCaptured`]. For the text field that captured it, like Escape
+    /// to stop typing.
+    pub fn just_typed(&self, action: InputAction) -> bool {
+        let bindings = self.input_map.bindings(action);
+        self.keys.any_just_pressed(bindings.keys.iter().copied())
+    }
+
+    /// The analog left stick of the active gamepad. It isn't rebindable, so
+    /// it's read directly rather than through an action.
+    pub fn left_stick(&self) -> Vec2 {
+        if self.blocked() {
+            return Vec2::ZERO;
+        }
+        self.gamepad.left_stick()
+    }
+
+    fn blocked_action(&self, action: InputAction) -> bool {
+        self.blocked() && action != InputAction::Pause
+    }
+}
+
+/// Run condition that is true on the frame an [`InputAction`] is pressed.
+pub fn action_just_pressed(action: InputAction) -> impl FnMut(ActionInput) -> bool + Clone {
+    move |input: ActionInput| input.just_pressed(action)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn merged(json: &str) -> InputMap {
+        let mut map = InputMap::default();
+        map.merge_saved(serde_json::from_str(json).unwrap());
+        map
+    }
+
+    #[test]
+    fn saved_bindings_replace_the_defaults() {
+        let map = merged(r#"{"Quack": {"keys": ["KeyK"], "gamepad_buttons": ["West"]}}"#);
+        assert_eq!(
+            *map.bindings(InputAction::Quack),
+            Bindings {
+                keys: vec![KeyCode::KeyK],
+                gamepad_buttons: vec![GamepadButtonType::West],
+            }
+        );
+        assert_eq!(
+            *map.bindings(InputAction::Pause),
+            InputAction::Pause.default_bindings()
+        );
+    }
+
+    #[test]
+    fn unknown_actions_are_skipped_without_losing_the_rest() {
+        let map = merged(
+            r#"{
+                "Teleport": {"keys": ["KeyT"], "gamepad_buttons": []},
+                "Quack": {"keys": ["KeyK"], "gamepad_buttons": []},
+                "Dash": {"keys": "not a list", "gamepad_buttons": []}
+            }"#,
+        );
+        assert_eq!(map.bindings(InputAction::Quack).keys, vec![KeyCode::KeyK]);
+        assert_eq!(
+            *map.bindings(InputAction::Dash),
+            InputAction::Dash.default_bindings()
+        );
+        assert_eq!(map.0.len(), InputAction::ALL.len());
+    }
+}
@@ -0,0 +1,10 @@
+//! Input actions and the devices that drive them, beyond the on-screen joystick.
+
+pub mod gamepad;
+pub mod input_map;
+
+use bevy::prelude::*;
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_plugins((gamepad::plugin, input_map::plugin));
+}
@@ -3,20 +3,18 @@ pub mod audio;
 mod demo;
 #[cfg(feature = "dev")]
 mod dev_tools;
+mod input;
+mod music;
+mod persistence;
 mod screens;
 mod theme;
 
 use bevy::{
     asset::AssetMetaCheck,
-    audio::{AudioPlugin, SpatialScale, Volume},
+    audio::{AudioPlugin, SpatialScale},
     prelude::*,
 };
 
-/// Spatial audio uses the distance to attenuate the sound volume. In 2D with the default camera,
-/// 1 pixel is 1 unit of distance, so we use a scale so that 100 pixels is 1 unit of distance for
-/// audio.
-const AUDIO_SCALE: f32 = 1. / 500.0;
-
 pub struct AppPlugin;
 
 impl Plugin for AppPlugin {
@@ -52,10 +50,8 @@ impl Plugin for AppPlugin {
                     ..default()
                 })
                 .set(AudioPlugin {
-                    global_volume: GlobalVolume {
-                        volume: Volume::new(0.3),
-                    },
-                    default_spatial_scale: SpatialScale::new_2d(AUDIO_SCALE),
+                    // The global volume follows the player's settings, see `audio`.
+                    default_spatial_scale: SpatialScale::new_2d(audio::AUDIO_SCALE),
                     ..default()
                 }),
         );
@@ -63,7 +59,10 @@ impl Plugin for AppPlugin {
         // Add other plugins.
         app.add_plugins((
             asset_tracking::plugin,
+            audio::plugin,
             demo::plugin,
+            input::plugin,
+            music::plugin,
             screens::plugin,
             theme::plugin,
         ));
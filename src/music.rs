@@ -0,0 +1,231 @@
+//! Background music for each screen.
+//!
+//! Screens give the [`MusicManager`] a [`Playlist`], and it plays the one for
+//! the current screen, crossfading whenever that changes. Screens that share a
+//! playlist keep the music going between them. Tracks play on
+//! [`AudioChannel::Music`], so they follow the music volume setting.
+
+use bevy::{audio::Volume, prelude::*, utils::HashMap};
+use rand::prelude::*;
+
+use crate::{audio::AudioChannel, screens::Screen, AppSet};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<MusicManager>();
+    // After screens have had a chance to change their playlists this frame.
+    app.add_systems(
+        Update,
+        (play_screen_music, fade_music_tracks)
+            .chain()
+            .after(AppSet::Update),
+    );
+}
+
+/// How long a crossfade between two tracks takes, in seconds.
+const CROSSFADE_SECONDS: f32 = 1.5;
+
+/// One piece of music, and how to play it.
+#[derive(Debug, Clone)]
+pub struct Track {
+    pub source: Handle<AudioSource>,
+    pub speed: f32,
+    /// Before the music volume setting.
+    pub volume: f32,
+}
+
+impl Track {
+    pub fn new(source: Handle<AudioSource>) -> Self {
+        Self {
+            source,
+            speed: 1.0,
+            volume: 1.0,
+        }
+    }
+
+    pub fn with_speed(mut self, speed: f32) -> Self {
+        self.speed = speed;
+        self
+    }
+}
+
+/// Tracks that play one after another, looping back to the start. A playlist
+/// with a single track loops it without a gap.
+#[derive(Debug, Clone)]
+pub struct Playlist {
+    /// Playlists with the same name are the same music, so switching between
+    /// them doesn't restart it.
+    pub name: &'static str,
+    pub tracks: Vec<Track>,
+    /// Play the tracks in a random order, shuffled again each time round.
+    pub shuffle: bool,
+}
+
+impl Playlist {
+    pub fn new(name: &'static str, tracks: impl IntoIterator<Item = Track>) -> Self {
+        Self {
+            name,
+            tracks: tracks.into_iter().collect(),
+            shuffle: false,
+        }
+    }
+
+    pub fn shuffled(mut self) -> Self {
+        self.shuffle = true;
+        self
+    }
+
+    /// The order to play the tracks in next time round. `last` is the track
+    /// that just played, which a shuffle won't repeat straight away.
+    fn order(&self, last: Option<usize>) -> Vec<usize> {
+        let mut order = (0..self.tracks.len()).collect::<Vec<_>>();
+        if self.shuffle {
+            order.shuffle(&mut rand::thread_rng());
+            if order.len() > 1 && order.first() == last.as_ref() {
+                order.swap(0, 1);
+            }
+        }
+        order
+    }
+}
+
+/// Plays the [`Playlist`] for the current [`Screen`]. Screens without one are
+/// silent.
+#[derive(Resource, Debug, Default)]
+pub struct MusicManager {
+    playlists: HashMap<Screen, Playlist>,
+    now_playing: Option<NowPlaying>,
+}
+
+impl MusicManager {
+    pub fn playlist(&self, screen: &Screen) -> Option<&Playlist> {
+        self.playlists.get(screen)
+    }
+
+    /// Sets the music for a screen. If it's the current screen, the music
+    /// crossfades to the new playlist.
+    pub fn set_playlist(&mut self, screen: Screen, playlist: Playlist) {
+        self.playlists.insert(screen, playlist);
+    }
+}
+
+#[derive(Debug)]
+struct NowPlaying {
+    playlist: Playlist,
+    order: Vec<usize>,
+    position: usize,
+    entity: Entity,
+}
+
+/// A playing track, faded in and out by the [`MusicManager`].
+#[derive(Component, Debug)]
+struct MusicTrack {
+    volume: f32,
+    /// From 0 to 1, how far the track has faded in.
+    fade: f32,
+    fading_out: bool,
+}
+
+fn play_screen_music(
+    mut commands: Commands,
+    screen: Option<Res<State<Screen>>>,
+    mut manager: ResMut<MusicManager>,
+    mut track_query: Query<(&mut MusicTrack, Option<&AudioSink>)>,
+) {
+    let wanted = screen.and_then(|screen| manager.playlist(screen.get()).cloned());
+    let playing_name = manager
+        .now_playing
+        .as_ref()
+        .map(|now_playing| now_playing.playlist.name);
+
+    if wanted.as_ref().map(|playlist| playlist.name) != playing_name {
+        if let Some(now_playing) = manager.now_playing.take() {
+            fade_out(&mut track_query, now_playing.entity);
+        }
+        manager.now_playing = wanted.and_then(|playlist| {
+            let order = playlist.order(None);
+            let entity = spawn_track(&mut commands, &playlist, order.first().copied()?);
+            Some(NowPlaying {
+                playlist,
+                order,
+                position: 0,
+                entity,
+            })
+        });
+        return;
+    }
+
+    // Move on to the next track once this one is over.
+    let Some(now_playing) = manager.now_playing.as_mut() else {
+        return;
+    };
+    let finished = track_query
+        .get(now_playing.entity)
+        .is_ok_and(|(_, sink)| sink.is_some_and(|sink| sink.empty()));
+    if !finished {
+        return;
+    }
+    fade_out(&mut track_query, now_playing.entity);
+    now_playing.position += 1;
+    if now_playing.position >= now_playing.order.len() {
+        let last = now_playing.order.last().copied();
+        now_playing.order = now_playing.playlist.order(last);
+        now_playing.position = 0;
+    }
+    let index = now_playing.order[now_playing.position];
+    now_playing.entity = spawn_track(&mut commands, &now_playing.playlist, index);
+}
+
+fn fade_out(track_query: &mut Query<(&mut MusicTrack, Option<&AudioSink>)>, entity: Entity) {
+    if let Ok((mut track, _)) = track_query.get_mut(entity) {
+        track.fading_out = true;
+    }
+}
+
+fn spawn_track(commands: &mut Commands, playlist: &Playlist, index: usize) -> Entity {
+    let track = &playlist.tracks[index];
+    let settings = if playlist.tracks.len() == 1 {
+        PlaybackSettings::LOOP
+    } else {
+        PlaybackSettings::ONCE
+    };
+    commands
+        .spawn((
+            Name::new(format!("Music: {}", playlist.name)),
+            AudioBundle {
+                source: track.source.clone(),
+                // Starts silent, and fades in.
+                settings: settings
+                    .with_volume(Volume::new(0.0))
+                    .with_speed(track.speed),
+            },
+            MusicTrack {
+                volume: track.volume,
+                fade: 0.0,
+                fading_out: false,
+            },
+            AudioChannel::Music,
+        ))
+        .id()
+}
+
+/// Sets the volume the tracks play at before the music volume setting, which
+/// is applied on top of this by the `audio` module.
+fn fade_music_tracks(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut track_query: Query<(Entity, &mut MusicTrack, &mut PlaybackSettings)>,
+) {
+    let step = time.delta_seconds() / CROSSFADE_SECONDS;
+    for (entity, mut track, mut settings) in &mut track_query {
+        if track.fading_out {
+            track.fade = (track.fade - step).max(0.0);
+            if track.fade == 0.0 {
+                commands.entity(entity).despawn_recursive();
+                continue;
+            }
+        } else {
+            track.fade = (track.fade + step).min(1.0);
+        }
+        settings.volume = Volume::new(track.volume * track.fade);
+    }
+}
@@ -0,0 +1,82 @@
+//! Saving small bits of player configuration between sessions.
+//!
+//! Values are stored as JSON: in a config file per key on native, and in
+//! `localStorage` on web.
+
+use bevy::prelude::*;
+use serde::{de::DeserializeOwned, Serialize};
+
+/// Load a previously [`save`]d value. Returns `None` if nothing was saved yet
+/// or the saved data can't be read any more, e.g. after a format change.
+pub fn load<T: DeserializeOwned>(key: &str) -> Option<T> {
+    let json = read(key)?;
+    serde_json::from_str(&json)
+        .map_err(|err| warn!("Ignoring saved {key}: {err}"))
+        .ok()
+}
+
+/// Save a value so that it can be [`load`]ed in a later session.
+pub fn save<T: Serialize>(key: &str, value: &T) {
+    match serde_json::to_string_pretty(value) {
+        Ok(json) => write(key, &json),
+        Err(err) => warn!("Couldn't serialize {key}: {err}"),
+    }
+}
+
+#[cfg(not(target_family = "wasm"))]
+fn config_path(key: &str) -> Option<std::path::PathBuf> {
+    let config_dir = std::env::var_os("XDG_CONFIG_HOME")
+        .map(std::path::PathBuf::from)
+        .or_else(|| std::env::var_os("APPDATA").map(std::path::PathBuf::from))
+        .or_else(|| {
+            std::env::var_os("HOME").map(|home| std::path::Path::new(&home).join(".config"))
+        })?;
+    Some(config_dir.join("ducks-test").join(format!("{key}.json")))
+}
+
+#[cfg(not(target_family = "wasm"))]
+fn read(key: &str) -> Option<String> {
+    std::fs::read_to_string(config_path(key)?).ok()
+}
+
+#[cfg(not(target_family = "wasm"))]
+fn write(key: &str, json: &str) {
+    let Some(path) = config_path(key) else {
+        warn!("No config directory to save {key} to");
+        return;
+    };
+    let result = path
+        .parent()
+        .map_or(Ok(()), std::fs::create_dir_all)
+        .and_then(|_| std::fs::write(&path, json));
+    if let Err(err) = result {
+        warn!("Couldn't save {key} to {}: {err}", path.display());
+    }
+}
+
+#[cfg(target_family = "wasm")]
+fn local_storage() -> Option<web_sys::Storage> {
+    web_sys::window()?.local_storage().ok().flatten()
+}
+
+#[cfg(target_family = "wasm")]
+fn read(key: &str) -> Option<String> {
+    local_storage()?
+        .get_item(&format!("ducks-test.{key}"))
+        .ok()
+        .flatten()
+}
+
+#[cfg(target_family = "wasm")]
+fn write(key: &str, json: &str) {
+    let Some(storage) = local_storage() else {
+        warn!("No localStorage to save {key} to");
+        return;
+    };
+    if storage
+        .set_item(&format!("ducks-test.{key}"), json)
+        .is_err()
+    {
+        warn!("Couldn't save {key} to localStorage");
+    }
+}
@@ -0,0 +1,212 @@
+//! A controls screen for rebinding keyboard keys and gamepad buttons.
+//! It can be accessed from the title screen.
+
+use bevy::prelude::*;
+
+use crate::{
+    input::input_map::{Bindings, InputAction, InputMap},
+    screens::Screen,
+    theme::prelude::*,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<Rebinding>();
+    app.init_resource::<RebindTimeout>();
+    app.add_systems(OnEnter(Screen::Controls), spawn_controls_screen);
+    app.add_systems(OnExit(Screen::Controls), stop_rebinding);
+    app.add_systems(
+        Update,
+        (time_out_rebinding, capture_rebind, update_binding_labels)
+            .chain()
+            .run_if(in_state(Screen::Controls)),
+    );
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BindingDevice {
+    Keyboard,
+    Gamepad,
+}
+
+/// A button on the controls screen that rebinds one action on one device.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+struct BindingButton {
+    action: InputAction,
+    device: BindingDevice,
+}
+
+/// The binding that is waiting for the player to press a new key or button.
+#[derive(Resource, Debug, Default)]
+struct Rebinding(Option<BindingButton>);
+
+/// How long to wait for a new key or button before giving up, in seconds.
+/// Every key and button can be bound, so there's none left to cancel with.
+const REBIND_TIMEOUT_SECONDS: f32 = 5.0;
+
+/// Counts down while [`Rebinding`] is waiting.
+#[derive(Resource, Debug)]
+struct RebindTimeout(Timer);
+
+impl Default for RebindTimeout {
+    fn default() -> Self {
+        Self(Timer::from_seconds(REBIND_TIMEOUT_SECONDS, TimerMode::Once))
+    }
+}
+
+fn spawn_controls_screen(mut commands: Commands) {
+    commands
+        .ui_root()
+        .insert(StateScoped(Screen::Controls))
+        .with_children(|children| {
+            children.header("Controls");
+
+            for action in InputAction::ALL {
+                children.ui_row().with_children(|row| {
+                    row.label(action.label()).insert(Style {
+                        width: Val::Px(180.0),
+                        align_self: AlignSelf::Center,
+                        ..default()
+                    });
+                    for device in [BindingDevice::Keyboard, BindingDevice::Gamepad] {
+                        row.small_button("")
+                            .insert(BindingButton { action, device })
+                            .observe(start_rebinding);
+                    }
+                });
+            }
+
+            children.ui_row().with_children(|row| {
+                row.button("Reset").observe(reset_bindings);
+                row.button("Back").observe(enter_title_screen);
+            });
+        });
+}
+
+fn start_rebinding(
+    trigger: Trigger<OnPress>,
+    binding_buttons: Query<&BindingButton>,
+    mut rebinding: ResMut<Rebinding>,
+    mut timeout: ResMut<RebindTimeout>,
+) {
+    if let Ok(binding_button) = binding_buttons.get(trigger.entity()) {
+        rebinding.0 = Some(*binding_button);
+        timeout.0.reset();
+    }
+}
+
+fn stop_rebinding(mut rebinding: ResMut<Rebinding>) {
+    rebinding.0 = None;
+}
+
+fn reset_bindings(_trigger: Trigger<OnPress>, mut input_map: ResMut<InputMap>) {
+    *input_map = InputMap::default();
+}
+
+fn enter_title_screen(_trigger: Trigger<OnPress>, mut next_screen: ResMut<NextState<Screen>>) {
+    next_screen.set(Screen::Title);
+}
+
+fn time_out_rebinding(
+    time: Res<Time>,
+    mut rebinding: ResMut<Rebinding>,
+    mut timeout: ResMut<RebindTimeout>,
+) {
+    if rebinding.0.is_none() {
+        return;
+    }
+    if timeout.0.tick(time.delta()).just_finished() {
+        rebinding.0 = None;
+    }
+}
+
+/// Bind the next key or gamepad button pressed to the action being rebound.
+fn capture_rebind(
+    mut rebinding: ResMut<Rebinding>,
+    mut input_map: ResMut<InputMap>,
+    keys: Res<ButtonInput<KeyCode>>,
+    gamepad_buttons: Res<ButtonInput<GamepadButton>>,
+) {
+    // Skip the frame rebinding started on, so that the click or button press
+    // that started it isn't captured as the new binding.
+    if rebinding.is_changed() {
+        return;
+    }
+    let Some(binding_button) = rebinding.0 else {
+        return;
+    };
+
+    // Only borrow the map mutably once there's something to bind, so it isn't
+    // saved every frame while waiting.
+    let action = binding_button.action;
+    match binding_button.device {
+        BindingDevice::Keyboard => {
+            let Some(key) = keys.get_just_pressed().next() else {
+                return;
+            };
+            input_map.rebind_key(action, *key);
+        }
+        BindingDevice::Gamepad => {
+            let Some(button) = gamepad_buttons.get_just_pressed().next() else {
+                return;
+            };
+            input_map.rebind_gamepad_button(action, button.button_type);
+        }
+    }
+    rebinding.0 = None;
+}
+
+fn update_binding_labels(
+    rebinding: Res<Rebinding>,
+    timeout: Res<RebindTimeout>,
+    input_map: Res<InputMap>,
+    binding_buttons: Query<(&BindingButton, &Children)>,
+    added: Query<(), Added<BindingButton>>,
+    mut text_query: Query<&mut Text>,
+) {
+    if !rebinding.is_changed()
+        && !timeout.is_changed()
+        && !input_map.is_changed()
+        && added.is_empty()
+    {
+        return;
+    }
+
+    for (binding_button, children) in &binding_buttons {
+        let label = if rebinding.0 == Some(*binding_button) {
+            format!("Press... {:.0}", timeout.0.remaining_secs().ceil())
+        } else {
+            binding_label(
+                input_map.bindings(binding_button.action),
+                binding_button.device,
+            )
+        };
+        let mut text_iter = text_query.iter_many_mut(children);
+        while let Some(mut text) = text_iter.fetch_next() {
+            text.sections[0].value = label.clone();
+        }
+    }
+}
+
+fn binding_label(bindings: &Bindings, device: BindingDevice) -> String {
+    let names: Vec<String> = match device {
+        BindingDevice::Keyboard => bindings.keys.iter().map(key_name).collect(),
+        BindingDevice::Gamepad => bindings
+            .gamepad_buttons
+            .iter()
+            .map(|button| format!("{button:?}"))
+            .collect(),
+    };
+    if names.is_empty() {
+        "--".to_string()
+    } else {
+        names.join(" / ")
+    }
+}
+
+fn key_name(key: &KeyCode) -> String {
+    let name = format!("{key:?}");
+    name.strip_prefix("Key")
+        .or_else(|| name.strip_prefix("Digit"))
+        .unwrap_or(&name)
+        .to_string()
+}
@@ -2,14 +2,21 @@
 
 use bevy::prelude::*;
 
-use crate::{asset_tracking::LoadResource, audio::Music, screens::Screen, theme::prelude::*};
+use crate::{
+    asset_tracking::LoadResource,
+    music::{MusicManager, Playlist, Track},
+    screens::Screen,
+    theme::prelude::*,
+};
 
 pub(super) fn plugin(app: &mut App) {
     app.add_systems(OnEnter(Screen::Credits), spawn_credits_screen);
 
     app.load_resource::<CreditsMusic>();
-    app.add_systems(OnEnter(Screen::Credits), play_credits_music);
-    app.add_systems(OnExit(Screen::Credits), stop_music);
+    app.add_systems(
+        Update,
+        set_credits_playlist.run_if(resource_added::<CreditsMusic>),
+    );
 }
 
 // TODO - Justify center the stuff here.
@@ -36,7 +43,6 @@ fn enter_title_screen(_trigger: Trigger<OnPress>, mut next_screen: ResMut<NextSt
 pub struct CreditsMusic {
     #[dependency]
     music: Handle<AudioSource>,
-    entity: Option<Entity>,
 }
 
 impl FromWorld for CreditsMusic {
@@ -44,27 +50,13 @@ impl FromWorld for CreditsMusic {
         let assets = world.resource::<AssetServer>();
         Self {
             music: assets.load("audio/music/Monkeys Spinning Monkeys.ogg"),
-            entity: None,
         }
     }
 }
 
-fn play_credits_music(mut commands: Commands, mut music: ResMut<CreditsMusic>) {
-    music.entity = Some(
-        commands
-            .spawn((
-                AudioBundle {
-                    source: music.music.clone(),
-                    settings: PlaybackSettings::LOOP,
-                },
-                Music,
-            ))
-            .id(),
+fn set_credits_playlist(music: Res<CreditsMusic>, mut manager: ResMut<MusicManager>) {
+    manager.set_playlist(
+        Screen::Credits,
+        Playlist::new("Credits", [Track::new(music.music.clone())]),
     );
 }
-
-fn stop_music(mut commands: Commands, mut music: ResMut<CreditsMusic>) {
-    if let Some(entity) = music.entity.take() {
-        commands.entity(entity).despawn_recursive();
-    }
-}
@@ -4,8 +4,8 @@
 use bevy::prelude::*;
 
 use crate::{
-    demo::player::PlayerAssets,
-    screens::{credits::CreditsMusic, gameplay::GameplayMusic, Screen},
+    demo::{day_night::DayNightMusic, level::LevelAssets, player::PlayerAssets},
+    screens::{credits::CreditsMusic, title::MenuMusic, Screen},
     theme::{interaction::InteractionAssets, prelude::*},
 };
 
@@ -37,11 +37,15 @@ fn continue_to_title_screen(mut next_screen: ResMut<NextState<Screen>>) {
 fn all_assets_loaded(
     player_assets: Option<Res<PlayerAssets>>,
     interaction_assets: Option<Res<InteractionAssets>>,
+    menu_music: Option<Res<MenuMusic>>,
     credits_music: Option<Res<CreditsMusic>>,
-    gameplay_music: Option<Res<GameplayMusic>>,
+    day_night_music: Option<Res<DayNightMusic>>,
+    level_assets: Option<Res<LevelAssets>>,
 ) -> bool {
     player_assets.is_some()
         && interaction_assets.is_some()
+        && menu_music.is_some()
         && credits_music.is_some()
-        && gameplay_music.is_some()
+        && day_night_music.is_some()
+        && level_assets.is_some()
 }
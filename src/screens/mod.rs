@@ -1,8 +1,11 @@
 //! The game's main screen states and transitions between them.
 
+mod controls;
 mod credits;
 mod gameplay;
 mod loading;
+mod pause;
+mod settings;
 mod splash;
 mod title;
 
@@ -13,9 +16,12 @@ pub(super) fn plugin(app: &mut App) {
     app.enable_state_scoped_entities::<Screen>();
 
     app.add_plugins((
+        controls::plugin,
         credits::plugin,
         gameplay::plugin,
         loading::plugin,
+        pause::plugin,
+        settings::plugin,
         splash::plugin,
         title::plugin,
     ));
@@ -29,5 +35,7 @@ pub enum Screen {
     Loading,
     Title,
     Credits,
+    Controls,
+    Settings,
     Gameplay,
 }
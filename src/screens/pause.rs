@@ -0,0 +1,119 @@
+//! The pause menu, opened over gameplay with [`InputAction::Pause`].
+//!
+//! The game is online, so the world keeps going while the menu is open. It only
+//! stops your input from reaching your duck. Leave Game tells the server you've
+//! left and closes the connection before going back to the title screen.
+
+use bevy::{ecs::system::EntityCommands, prelude::*, ui::FocusPolicy};
+
+use crate::{
+    demo::{chat::ChatInputSet, websocket_leave_msg::LeaveRequestEvent},
+    input::input_map::{action_just_pressed, GameplayInputBlocked, InputAction},
+    screens::{
+        settings::{spawn_settings, CurrentSettings},
+        Screen,
+    },
+    theme::prelude::*,
+    AppSet,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_sub_state::<Menu>();
+    app.enable_state_scoped_entities::<Menu>();
+
+    app.add_systems(OnEnter(Menu::Pause), spawn_pause_menu);
+    app.add_systems(OnEnter(Menu::Settings), spawn_settings_menu);
+    app.add_systems(
+        Update,
+        (
+            // Before the chat input lets go of the keyboard, so the Escape
+            // that stops typing doesn't also open the menu.
+            toggle_pause_menu
+                .run_if(
+                    in_state(Screen::Gameplay).and_then(action_just_pressed(InputAction::Pause)),
+                )
+                .before(ChatInputSet)
+                .in_set(AppSet::RecordInput),
+            block_gameplay_input,
+        ),
+    );
+}
+
+/// The menu open over gameplay, if any.
+#[derive(SubStates, Debug, Hash, PartialEq, Eq, Clone, Copy, Default)]
+#[source(Screen = Screen::Gameplay)]
+pub enum Menu {
+    #[default]
+    None,
+    Pause,
+    Settings,
+}
+
+/// Dims the game behind the menu.
+const BACKDROP_COLOR: Color = Color::srgba(0.0, 0.0, 0.0, 0.6);
+
+fn toggle_pause_menu(menu: Res<State<Menu>>, mut next_menu: ResMut<NextState<Menu>>) {
+    next_menu.set(match menu.get() {
+        Menu::None => Menu::Pause,
+        Menu::Pause => Menu::None,
+        // Back out one page at a time.
+        Menu::Settings => Menu::Pause,
+    });
+}
+
+fn block_gameplay_input(menu: Option<Res<State<Menu>>>, mut blocked: ResMut<GameplayInputBlocked>) {
+    let open = menu.is_some_and(|menu| *menu.get() != Menu::None);
+    if blocked.0 != open {
+        blocked.0 = open;
+    }
+}
+
+/// A full screen root over the rest of the UI that clicks don't go through.
+fn spawn_menu_root<'a>(commands: &'a mut Commands, menu: Menu) -> EntityCommands<'a> {
+    let mut root = commands.ui_root();
+    root.insert((
+        StateScoped(menu),
+        BackgroundColor(BACKDROP_COLOR),
+        FocusPolicy::Block,
+        ZIndex::Global(10),
+    ));
+    root
+}
+
+fn spawn_pause_menu(mut commands: Commands) {
+    spawn_menu_root(&mut commands, Menu::Pause).with_children(|children| {
+        children.header("Paused");
+        children.button("Resume").observe(resume);
+        children.button("Settings").observe(open_settings);
+        children.button("Leave Game").observe(leave_game);
+    });
+}
+
+fn spawn_settings_menu(mut commands: Commands, settings: CurrentSettings) {
+    spawn_menu_root(&mut commands, Menu::Settings).with_children(|children| {
+        children.header("Settings");
+        spawn_settings(children, &settings);
+        children.button("Back").observe(back_to_pause_menu);
+    });
+}
+
+fn resume(_trigger: Trigger<OnPress>, mut next_menu: ResMut<NextState<Menu>>) {
+    next_menu.set(Menu::None);
+}
+
+fn open_settings(_trigger: Trigger<OnPress>, mut next_menu: ResMut<NextState<Menu>>) {
+    next_menu.set(Menu::Settings);
+}
+
+fn back_to_pause_menu(_trigger: Trigger<OnPress>, mut next_menu: ResMut<NextState<Menu>>) {
+    next_menu.set(Menu::Pause);
+}
+
+fn leave_game(
+    _trigger: Trigger<OnPress>,
+    mut leave_request_event_writer: EventWriter<LeaveRequestEvent>,
+    mut next_screen: ResMut<NextState<Screen>>,
+) {
+    leave_request_event_writer.send(LeaveRequestEvent);
+    next_screen.set(Screen::Title);
+}
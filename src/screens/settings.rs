@@ -0,0 +1,193 @@
+//! The settings screen, for audio volumes and a few game options. It can be
+//! reached from the title screen, and from the pause menu during a game.
+//!
+//! Changes apply right away, and are saved between sessions by the resources
+//! they change.
+
+use bevy::{ecs::system::SystemParam, prelude::*};
+
+use crate::{
+    audio::{AudioSettings, VolumeChannel},
+    demo::{chat::ChatSettings, minimap::ShowMinimap},
+    screens::Screen,
+    theme::prelude::*,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(Screen::Settings), spawn_settings_screen);
+    app.add_systems(
+        Update,
+        (apply_volume_sliders, update_settings_widgets).chain(),
+    );
+}
+
+/// The slider for one [`VolumeChannel`].
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+struct VolumeSlider(VolumeChannel);
+
+/// The mute button for one [`VolumeChannel`].
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+struct MuteButton(VolumeChannel);
+
+/// A button that flips a game option, and shows its value.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+enum SettingToggle {
+    ChatScope,
+    Minimap,
+}
+
+impl SettingToggle {
+    const ALL: [Self; 2] = [Self::ChatScope, Self::Minimap];
+}
+
+/// Everything the settings widgets show.
+#[derive(SystemParam)]
+pub(super) struct CurrentSettings<'w> {
+    audio: Res<'w, AudioSettings>,
+    chat: Res<'w, ChatSettings>,
+    show_minimap: Res<'w, ShowMinimap>,
+}
+
+impl CurrentSettings<'_> {
+    fn is_changed(&self) -> bool {
+        self.audio.is_changed() || self.chat.is_changed() || self.show_minimap.is_changed()
+    }
+
+    fn mute_label(&self, channel: VolumeChannel) -> &'static str {
+        if self.audio.muted(channel) {
+            "Unmute"
+        } else {
+            "Mute"
+        }
+    }
+
+    fn toggle_label(&self, toggle: SettingToggle) -> &'static str {
+        match toggle {
+            SettingToggle::ChatScope if self.chat.proximity_only => "Chat: Nearby",
+            SettingToggle::ChatScope => "Chat: Everyone",
+            SettingToggle::Minimap if self.show_minimap.0 => "Minimap: On",
+            SettingToggle::Minimap => "Minimap: Off",
+        }
+    }
+}
+
+/// Spawns the settings widgets. Used by both the settings screen and the pause
+/// menu, which add their own way back.
+pub(super) fn spawn_settings(children: &mut ChildBuilder, settings: &CurrentSettings) {
+    for channel in VolumeChannel::ALL {
+        children.ui_row().with_children(|row| {
+            row.label(channel.label()).insert(Style {
+                width: Val::Px(180.0),
+                align_self: AlignSelf::Center,
+                ..default()
+            });
+            row.slider(settings.audio.volume(channel))
+                .insert(VolumeSlider(channel));
+            row.small_button(settings.mute_label(channel))
+                .insert((
+                    MuteButton(channel),
+                    Style {
+                        width: Val::Px(120.0),
+                        height: Val::Px(40.0),
+                        justify_content: JustifyContent::Center,
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                ))
+                .observe(toggle_mute);
+        });
+    }
+
+    for toggle in SettingToggle::ALL {
+        children
+            .small_button(settings.toggle_label(toggle))
+            .insert(toggle)
+            .observe(flip_setting);
+    }
+}
+
+fn spawn_settings_screen(mut commands: Commands, settings: CurrentSettings) {
+    commands
+        .ui_root()
+        .insert(StateScoped(Screen::Settings))
+        .with_children(|children| {
+            children.header("Settings");
+            spawn_settings(children, &settings);
+            children.button("Back").observe(enter_title_screen);
+        });
+}
+
+fn enter_title_screen(_trigger: Trigger<OnPress>, mut next_screen: ResMut<NextState<Screen>>) {
+    next_screen.set(Screen::Title);
+}
+
+fn toggle_mute(
+    trigger: Trigger<OnPress>,
+    mute_buttons: Query<&MuteButton>,
+    mut audio: ResMut<AudioSettings>,
+) {
+    if let Ok(MuteButton(channel)) = mute_buttons.get(trigger.entity()) {
+        let muted = audio.muted(*channel);
+        audio.set_muted(*channel, !muted);
+    }
+}
+
+fn flip_setting(
+    trigger: Trigger<OnPress>,
+    toggles: Query<&SettingToggle>,
+    mut chat: ResMut<ChatSettings>,
+    mut show_minimap: ResMut<ShowMinimap>,
+) {
+    match toggles.get(trigger.entity()) {
+        Ok(SettingToggle::ChatScope) => chat.proximity_only = !chat.proximity_only,
+        Ok(SettingToggle::Minimap) => show_minimap.0 = !show_minimap.0,
+        Err(_) => {}
+    }
+}
+
+fn apply_volume_sliders(
+    slider_query: Query<(&VolumeSlider, &Slider), Changed<Slider>>,
+    mut audio: ResMut<AudioSettings>,
+) {
+    for (VolumeSlider(channel), slider) in &slider_query {
+        if audio.volume(*channel) != slider.0 {
+            audio.set_volume(*channel, slider.0);
+        }
+    }
+}
+
+/// Keep the widgets in line with the settings, which can also change from
+/// elsewhere, like the chat scope button on the HUD.
+fn update_settings_widgets(
+    settings: CurrentSettings,
+    mut slider_query: Query<(&VolumeSlider, &mut Slider)>,
+    mute_query: Query<(&MuteButton, &Children)>,
+    toggle_query: Query<(&SettingToggle, &Children)>,
+    mut text_query: Query<&mut Text>,
+) {
+    if !settings.is_changed() {
+        return;
+    }
+
+    for (VolumeSlider(channel), mut slider) in &mut slider_query {
+        slider.set_if_neq(Slider(settings.audio.volume(*channel)));
+    }
+
+    let labels = mute_query
+        .iter()
+        .map(|(MuteButton(channel), children)| (settings.mute_label(*channel), children))
+        .chain(
+            toggle_query
+                .iter()
+                .map(|(toggle, children)| (settings.toggle_label(*toggle), children)),
+        );
+    for (label, children) in labels {
+        for child in children {
+            if let Ok(mut text) = text_query.get_mut(*child) {
+                if text.sections[0].value != label {
+                    text.sections[0].value = label.to_string();
+                }
+            }
+        }
+    }
+}
@@ -2,19 +2,74 @@
 
 use bevy::prelude::*;
 
-use crate::{screens::Screen, theme::prelude::*};
+use crate::{
+    asset_tracking::LoadResource,
+    demo::duck_color::{DuckColorChoice, DUCK_COLOR_PRESETS},
+    music::{MusicManager, Playlist, Track},
+    screens::Screen,
+    theme::prelude::*,
+};
 
 pub(super) fn plugin(app: &mut App) {
     app.add_systems(OnEnter(Screen::Title), spawn_title_screen);
+
+    app.load_resource::<MenuMusic>();
+    app.add_systems(
+        Update,
+        set_menu_playlist.run_if(resource_added::<MenuMusic>),
+    );
+    app.add_systems(
+        Update,
+        highlight_chosen_swatch
+            .run_if(in_state(Screen::Title).and_then(resource_changed::<DuckColorChoice>)),
+    );
+}
+
+/// Screens reached from the title screen, which keep its music playing.
+const MENU_SCREENS: [Screen; 3] = [Screen::Title, Screen::Controls, Screen::Settings];
+
+#[derive(Resource, Asset, Reflect, Clone)]
+pub struct MenuMusic {
+    #[dependency]
+    tracks: Vec<Handle<AudioSource>>,
+}
+
+impl FromWorld for MenuMusic {
+    fn from_world(world: &mut World) -> Self {
+        let assets = world.resource::<AssetServer>();
+        Self {
+            tracks: vec![
+                assets.load("audio/music/Fluffing A Duck.ogg"),
+                assets.load("audio/music/Monkeys Spinning Monkeys.ogg"),
+            ],
+        }
+    }
 }
 
-fn spawn_title_screen(mut commands: Commands) {
+/// A swatch in the duck colour picker, holding the preset name it selects.
+#[derive(Component)]
+struct DuckColorSwatch(&'static str);
+
+fn spawn_title_screen(mut commands: Commands, duck_color: Res<DuckColorChoice>) {
     commands
         .ui_root()
         .insert(StateScoped(Screen::Title))
         .with_children(|children| {
-            
+            children.label("Pick your duck colour").insert(Style {
+                justify_content: JustifyContent::Center,
+                ..default()
+            });
+            children.ui_row().with_children(|row| {
+                for (name, color) in DUCK_COLOR_PRESETS {
+                    row.swatch(color)
+                        .insert((DuckColorSwatch(name), swatch_border(name, &duck_color)))
+                        .observe(choose_duck_color);
+                }
+            });
+
             children.button("Play").observe(enter_gameplay_screen);
+            children.button("Settings").observe(enter_settings_screen);
+            children.button("Controls").observe(enter_controls_screen);
             children.button("Credits").observe(enter_credits_screen);
 
             #[cfg(not(target_family = "wasm"))]
@@ -22,11 +77,53 @@ fn spawn_title_screen(mut commands: Commands) {
         });
 }
 
+fn set_menu_playlist(music: Res<MenuMusic>, mut manager: ResMut<MusicManager>) {
+    let playlist = Playlist::new("Menu", music.tracks.iter().cloned().map(Track::new)).shuffled();
+    for screen in MENU_SCREENS {
+        manager.set_playlist(screen, playlist.clone());
+    }
+}
+
+fn choose_duck_color(
+    trigger: Trigger<OnPress>,
+    swatches: Query<&DuckColorSwatch>,
+    mut duck_color: ResMut<DuckColorChoice>,
+) {
+    if let Ok(swatch) = swatches.get(trigger.entity()) {
+        duck_color.0 = swatch.0.to_string();
+    }
+}
+
+fn highlight_chosen_swatch(
+    duck_color: Res<DuckColorChoice>,
+    mut swatches: Query<(&DuckColorSwatch, &mut BorderColor)>,
+) {
+    for (swatch, mut border) in &mut swatches {
+        *border = swatch_border(swatch.0, &duck_color);
+    }
+}
+
+fn swatch_border(name: &str, duck_color: &DuckColorChoice) -> BorderColor {
+    if name == duck_color.0 {
+        BorderColor(Color::WHITE)
+    } else {
+        BorderColor(Color::NONE)
+    }
+}
+
 fn enter_gameplay_screen(_trigger: Trigger<OnPress>, mut next_screen: ResMut<NextState<Screen>>) {
     
     next_screen.set(Screen::Gameplay);
 }
 
+fn enter_settings_screen(_trigger: Trigger<OnPress>, mut next_screen: ResMut<NextState<Screen>>) {
+    next_screen.set(Screen::Settings);
+}
+
+fn enter_controls_screen(_trigger: Trigger<OnPress>, mut next_screen: ResMut<NextState<Screen>>) {
+    next_screen.set(Screen::Controls);
+}
+
 fn enter_credits_screen(_trigger: Trigger<OnPress>, mut next_screen: ResMut<NextState<Screen>>) {
     next_screen.set(Screen::Credits);
 }
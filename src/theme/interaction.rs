@@ -1,6 +1,9 @@
 use bevy::prelude::*;
 
-use crate::{asset_tracking::LoadResource, audio::SoundEffect};
+use crate::{
+    asset_tracking::LoadResource,
+    audio::{AudioChannel, PlaySfx, SfxPriority},
+};
 
 pub(super) fn plugin(app: &mut App) {
     app.register_type::<InteractionPalette>();
@@ -85,20 +88,15 @@ impl FromWorld for InteractionAssets {
 fn trigger_interaction_sound_effect(
     interaction_query: Query<&Interaction, Changed<Interaction>>,
     interaction_assets: Res<InteractionAssets>,
-    mut commands: Commands,
+    mut play_sfx: EventWriter<PlaySfx>,
 ) {
     for interaction in &interaction_query {
-        let source = match interaction {
-            Interaction::Hovered => interaction_assets.hover.clone(),
-            Interaction::Pressed => interaction_assets.press.clone(),
+        // Presses shouldn't be drowned out by hovering over other buttons.
+        let (source, priority) = match interaction {
+            Interaction::Hovered => (interaction_assets.hover.clone(), SfxPriority::Low),
+            Interaction::Pressed => (interaction_assets.press.clone(), SfxPriority::Normal),
             _ => continue,
         };
-        commands.spawn((
-            AudioBundle {
-                source,
-                settings: PlaybackSettings::DESPAWN,
-            },
-            SoundEffect,
-        ));
+        play_sfx.send(PlaySfx::new(AudioChannel::Ui, source).with_priority(priority));
     }
 }
@@ -4,14 +4,18 @@
 #![allow(dead_code)]
 
 pub mod interaction;
+pub mod navigation;
 pub mod palette;
+pub mod slider;
 mod widgets;
 
 #[allow(unused_imports)]
 pub mod prelude {
     pub use super::{
         interaction::{InteractionPalette, OnPress},
+        navigation::Focusable,
         palette as ui_palette,
+        slider::Slider,
         widgets::{Containers as _, Widgets as _},
     };
 }
@@ -19,5 +23,5 @@ pub mod prelude {
 use bevy::prelude::*;
 
 pub(super) fn plugin(app: &mut App) {
-    app.add_plugins(interaction::plugin);
+    app.add_plugins((interaction::plugin, navigation::plugin, slider::plugin));
 }
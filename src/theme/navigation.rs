@@ -0,0 +1,166 @@
+//! Moving between buttons with a gamepad.
+//!
+//! The d-pad or left stick moves focus to the nearest [`Focusable`] widget in
+//! that direction, and the south face button (A on Xbox, Cross on PlayStation)
+//! presses it by triggering [`OnPress`], just like a click would. Left and
+//! right step a focused [`Slider`] instead.
+
+use std::time::Duration;
+
+use bevy::prelude::*;
+
+use crate::{
+    input::gamepad::ActiveGamepadInput,
+    theme::{interaction::OnPress, slider::Slider},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<MenuFocus>();
+    app.init_resource::<NavigationCooldown>();
+    app.add_systems(
+        Update,
+        (
+            tick_navigation_cooldown,
+            drop_stale_focus,
+            navigate_menu,
+            press_focused,
+            show_focus_outline,
+        )
+            .chain(),
+    );
+}
+
+/// Marks a UI widget as reachable by gamepad navigation.
+#[derive(Component, Debug, Default)]
+pub struct Focusable;
+
+/// The widget that currently has gamepad focus.
+#[derive(Resource, Debug, Default)]
+struct MenuFocus(Option<Entity>);
+
+/// Holding the stick shouldn't race through the whole menu in a few frames.
+#[derive(Resource, Debug)]
+struct NavigationCooldown(Timer);
+
+impl Default for NavigationCooldown {
+    fn default() -> Self {
+        // Start finished so the first input moves focus straight away.
+        let duration = Duration::from_millis(200);
+        let mut timer = Timer::new(duration, TimerMode::Once);
+        timer.tick(duration);
+        Self(timer)
+    }
+}
+
+const FOCUS_OUTLINE_COLOR: Color = Color::WHITE;
+/// How much one step left or right changes a focused slider.
+const SLIDER_STEP: f32 = 0.1;
+
+fn tick_navigation_cooldown(time: Res<Time>, mut cooldown: ResMut<NavigationCooldown>) {
+    cooldown.0.tick(time.delta());
+}
+
+/// Forget the focused widget when it's despawned, e.g. on a screen change.
+fn drop_stale_focus(mut focus: ResMut<MenuFocus>, focusables: Query<(), With<Focusable>>) {
+    if focus.0.is_some_and(|entity| !focusables.contains(entity)) {
+        focus.0 = None;
+    }
+}
+
+fn navigate_menu(
+    gamepad: ActiveGamepadInput,
+    mut focus: ResMut<MenuFocus>,
+    mut cooldown: ResMut<NavigationCooldown>,
+    focusables: Query<(Entity, &GlobalTransform, &InheritedVisibility), With<Focusable>>,
+    mut slider_query: Query<&mut Slider>,
+) {
+    let direction = gamepad.movement();
+    if direction == Vec2::ZERO {
+        // Releasing the stick allows tapping quickly through a menu.
+        let duration = cooldown.0.duration();
+        cooldown.0.tick(duration);
+        return;
+    }
+    if !cooldown.0.finished() {
+        return;
+    }
+
+    if direction.x.abs() > direction.y.abs() {
+        if let Some(mut slider) = focus.0.and_then(|entity| slider_query.get_mut(entity).ok()) {
+            let value = (slider.0 + SLIDER_STEP * direction.x.signum()).clamp(0.0, 1.0);
+            slider.set_if_neq(Slider(value));
+            cooldown.0.reset();
+            return;
+        }
+    }
+
+    let visible = focusables
+        .iter()
+        .filter(|(_, _, visibility)| visibility.get());
+    let current = focus.0.and_then(|entity| focusables.get(entity).ok());
+
+    let next = match current {
+        // UI coordinates grow downwards, gamepad coordinates grow upwards.
+        Some((entity, transform, _)) => {
+            let from = transform.translation().truncate();
+            let direction = Vec2::new(direction.x, -direction.y).normalize();
+            visible
+                .filter(|(other, _, _)| *other != entity)
+                .filter_map(|(other, transform, _)| {
+                    let offset = transform.translation().truncate() - from;
+                    let along = offset.dot(direction);
+                    if along <= 0.0 {
+                        return None;
+                    }
+                    // Prefer widgets straight ahead over ones off to the side.
+                    let across = offset.perp_dot(direction).abs();
+                    Some((other, along + 2.0 * across))
+                })
+                .min_by(|(_, a), (_, b)| a.total_cmp(b))
+                .map(|(other, _)| other)
+        }
+        // Nothing focused yet, so start at the top.
+        None => visible
+            .min_by(|(_, a, _), (_, b, _)| {
+                let a = a.translation();
+                let b = b.translation();
+                a.y.total_cmp(&b.y).then(a.x.total_cmp(&b.x))
+            })
+            .map(|(entity, _, _)| entity),
+    };
+
+    if let Some(next) = next {
+        focus.0 = Some(next);
+        cooldown.0.reset();
+    }
+}
+
+fn press_focused(gamepad: ActiveGamepadInput, focus: Res<MenuFocus>, mut commands: Commands) {
+    if let Some(entity) = focus.0 {
+        if gamepad.just_pressed(GamepadButtonType::South) {
+            commands.trigger_targets(OnPress, entity);
+        }
+    }
+}
+
+fn show_focus_outline(
+    mut commands: Commands,
+    focus: Res<MenuFocus>,
+    outlined: Query<Entity, (With<Focusable>, With<Outline>)>,
+) {
+    if !focus.is_changed() {
+        return;
+    }
+    for entity in &outlined {
+        if Some(entity) != focus.0 {
+            commands.entity(entity).remove::<Outline>();
+        }
+    }
+    if let Some(entity) = focus.0 {
+        commands.entity(entity).insert(Outline::new(
+            Val::Px(3.0),
+            Val::Px(2.0),
+            FOCUS_OUTLINE_COLOR,
+        ));
+    }
+}
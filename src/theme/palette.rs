@@ -8,3 +8,5 @@ pub const LABEL_TEXT: Color = Color::srgb(0.867, 0.827, 0.412);
 pub const HEADER_TEXT: Color = Color::srgb(0.867, 0.827, 0.412);
 
 pub const NODE_BACKGROUND: Color = Color::srgb(0.286, 0.478, 0.773);
+
+pub const SLIDER_TRACK_BACKGROUND: Color = Color::srgb(0.12, 0.16, 0.24);
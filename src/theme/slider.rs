@@ -0,0 +1,46 @@
+//! A horizontal slider for picking a value from 0 to 1 by clicking or dragging
+//! along it, or stepping it left and right with a gamepad once it has focus.
+
+use bevy::{prelude::*, ui::RelativeCursorPosition};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<Slider>();
+    app.add_systems(Update, (drag_sliders, draw_slider_fill).chain());
+}
+
+/// The value of a slider widget, from 0 to 1. Spawn one with
+/// [`Widgets::slider`](super::widgets::Widgets::slider), and watch for
+/// changes to this component to react to it.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Reflect)]
+#[reflect(Component)]
+pub struct Slider(pub f32);
+
+/// The filled part of a slider, up to its value.
+#[derive(Component, Debug, Default)]
+pub(super) struct SliderFill;
+
+fn drag_sliders(mut slider_query: Query<(&Interaction, &RelativeCursorPosition, &mut Slider)>) {
+    for (interaction, cursor, mut slider) in &mut slider_query {
+        // Stays pressed while dragging off the end, so the value clamps.
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        let Some(position) = cursor.normalized else {
+            continue;
+        };
+        slider.set_if_neq(Slider(position.x.clamp(0.0, 1.0)));
+    }
+}
+
+fn draw_slider_fill(
+    slider_query: Query<(&Slider, &Children), Changed<Slider>>,
+    mut fill_query: Query<&mut Style, With<SliderFill>>,
+) {
+    for (slider, children) in &slider_query {
+        for child in children {
+            if let Ok(mut style) = fill_query.get_mut(*child) {
+                style.width = Val::Percent(slider.0 * 100.0);
+            }
+        }
+    }
+}
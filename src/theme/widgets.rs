@@ -1,8 +1,17 @@
 //! Helper traits for creating common widgets.
 
-use bevy::{ecs::system::EntityCommands, prelude::*, ui::Val::*};
+use bevy::{
+    ecs::system::EntityCommands,
+    prelude::*,
+    ui::{RelativeCursorPosition, Val::*},
+};
 
-use crate::theme::{interaction::InteractionPalette, palette::*};
+use crate::theme::{
+    interaction::InteractionPalette,
+    navigation::Focusable,
+    palette::*,
+    slider::{Slider, SliderFill},
+};
 
 // Component to hold the current text input
 #[derive(Component)]
@@ -15,12 +24,21 @@ pub trait Widgets {
     /// Spawn a simple button with text.
     fn button(&mut self, text: impl Into<String>) -> EntityCommands;
 
+    /// Spawn a smaller button with text, for dense menus like the controls screen.
+    fn small_button(&mut self, text: impl Into<String>) -> EntityCommands;
+
     /// Spawn a simple header label. Bigger than [`Widgets::label`].
     fn header(&mut self, text: impl Into<String>) -> EntityCommands;
 
     /// Spawn a simple text label.
     fn label(&mut self, text: impl Into<String>) -> EntityCommands;
 
+    /// Spawn a square colour swatch button.
+    fn swatch(&mut self, color: Color) -> EntityCommands;
+
+    /// Spawn a horizontal [`Slider`] set to `value`, from 0 to 1.
+    fn slider(&mut self, value: f32) -> EntityCommands;
+
     // Spawn a text input.
     // fn text_input(&mut self, text: impl Into<String>) -> EntityCommands;
 }
@@ -45,6 +63,7 @@ impl<T: Spawn> Widgets for T {
                 hovered: BUTTON_HOVERED_BACKGROUND,
                 pressed: BUTTON_PRESSED_BACKGROUND,
             },
+            Focusable,
         ));
         entity.with_children(|children| {
             children.spawn((
@@ -63,6 +82,44 @@ impl<T: Spawn> Widgets for T {
         entity
     }
 
+    fn small_button(&mut self, text: impl Into<String>) -> EntityCommands {
+        let mut entity = self.spawn((
+            Name::new("Small Button"),
+            ButtonBundle {
+                style: Style {
+                    width: Px(220.0),
+                    height: Px(40.0),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                background_color: BackgroundColor(NODE_BACKGROUND),
+                ..default()
+            },
+            InteractionPalette {
+                none: NODE_BACKGROUND,
+                hovered: BUTTON_HOVERED_BACKGROUND,
+                pressed: BUTTON_PRESSED_BACKGROUND,
+            },
+            Focusable,
+        ));
+        entity.with_children(|children| {
+            children.spawn((
+                Name::new("Button Text"),
+                TextBundle::from_section(
+                    text,
+                    TextStyle {
+                        font_size: 22.0,
+                        color: BUTTON_TEXT,
+                        ..default()
+                    },
+                ),
+            ));
+        });
+
+        entity
+    }
+
     fn header(&mut self, text: impl Into<String>) -> EntityCommands {
         let mut entity = self.spawn((
             Name::new("Header"),
@@ -112,6 +169,61 @@ impl<T: Spawn> Widgets for T {
         ));
         entity
     }
+
+    fn swatch(&mut self, color: Color) -> EntityCommands {
+        let entity = self.spawn((
+            Name::new("Swatch"),
+            ButtonBundle {
+                style: Style {
+                    width: Px(40.0),
+                    height: Px(40.0),
+                    border: UiRect::all(Px(4.0)),
+                    ..default()
+                },
+                background_color: BackgroundColor(color),
+                border_color: BorderColor(Color::NONE),
+                ..default()
+            },
+            Focusable,
+        ));
+        entity
+    }
+
+    fn slider(&mut self, value: f32) -> EntityCommands {
+        let mut entity = self.spawn((
+            Name::new("Slider"),
+            NodeBundle {
+                style: Style {
+                    width: Px(220.0),
+                    height: Px(20.0),
+                    align_self: AlignSelf::Center,
+                    ..default()
+                },
+                background_color: BackgroundColor(SLIDER_TRACK_BACKGROUND),
+                ..default()
+            },
+            Interaction::default(),
+            RelativeCursorPosition::default(),
+            Slider(value),
+            Focusable,
+        ));
+        entity.with_children(|children| {
+            children.spawn((
+                Name::new("Slider Fill"),
+                SliderFill,
+                NodeBundle {
+                    style: Style {
+                        width: Percent(value * 100.0),
+                        height: Percent(100.0),
+                        ..default()
+                    },
+                    background_color: BackgroundColor(NODE_BACKGROUND),
+                    ..default()
+                },
+            ));
+        });
+        entity
+    }
 }
 
 /// An extension trait for spawning UI containers.
@@ -119,9 +231,12 @@ pub trait Containers {
     /// Spawns a root node that covers the full screen
     /// and centers its content horizontally and vertically.
     fn ui_root(&mut self) -> EntityCommands;
+
+    /// Spawns a node that lays out its children in a horizontal row.
+    fn ui_row(&mut self) -> EntityCommands;
 }
 
-impl Containers for Commands<'_, '_> {
+impl<T: Spawn> Containers for T {
     fn ui_root(&mut self) -> EntityCommands {
         self.spawn((
             Name::new("UI Root"),
@@ -140,6 +255,21 @@ impl Containers for Commands<'_, '_> {
             },
         ))
     }
+
+    fn ui_row(&mut self) -> EntityCommands {
+        self.spawn((
+            Name::new("UI Row"),
+            NodeBundle {
+                style: Style {
+                    flex_direction: FlexDirection::Row,
+                    justify_content: JustifyContent::Center,
+                    column_gap: Px(10.0),
+                    ..default()
+                },
+                ..default()
+            },
+        ))
+    }
 }
 
 /// An internal trait for types that can spawn entities.